@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use retro_dat::DatReader;
+use std::fmt::Write;
+
+fn generate(games: usize) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\"?>\n<datafile>\n    <header>\n        <name>Bench</name>\n    </header>\n",
+    );
+    for i in 0..games {
+        write!(
+            xml,
+            r#"    <game name="Game {0}">
+        <description>Game {0} (World)</description>
+        <rom name="Game {0}.bin" size="{1}" crc="{0:08x}" md5="d41d8cd98f00b204e9800998ecf8427e" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709" status="verified"/>
+        <rom name="Game {0}.sav" size="32768" status="nodump"/>
+    </game>
+"#,
+            i,
+            1024 + i
+        )
+        .unwrap();
+    }
+    xml.push_str("</datafile>\n");
+    xml
+}
+
+fn read(c: &mut Criterion) {
+    let xml = generate(100_000);
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(xml.len() as u64));
+    group.sample_size(10);
+    group.bench_function("from_reader", |b| {
+        b.iter(|| DatReader::from_reader(xml.as_bytes()).read_all().unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read);
+criterion_main!(benches);
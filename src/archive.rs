@@ -0,0 +1,121 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek},
+    path::Path,
+};
+use zip::ZipArchive;
+
+use crate::{DatReader, DatReaderError};
+
+impl DatReader<Cursor<Vec<u8>>> {
+    /// Reads a DAT out of a zip archive.
+    ///
+    /// If `entry` is `None`, the archive must contain exactly one `.dat` or `.xml` file.
+    pub fn from_zip<P: AsRef<Path>>(
+        path: P,
+        entry: Option<&str>,
+    ) -> Result<DatReader<Cursor<Vec<u8>>>, DatReaderError> {
+        let file = File::open(path).map_err(quick_xml::Error::from)?;
+        DatReader::from_zip_reader(file, entry)
+    }
+    pub fn from_zip_reader<R: Read + Seek>(
+        reader: R,
+        entry: Option<&str>,
+    ) -> Result<DatReader<Cursor<Vec<u8>>>, DatReaderError> {
+        let buf = read_zip_entry(reader, entry)?;
+        Ok(DatReader::from_reader(Cursor::new(buf)))
+    }
+}
+
+pub(crate) fn read_zip_entry<R: Read + Seek>(
+    reader: R,
+    entry: Option<&str>,
+) -> Result<Vec<u8>, DatReaderError> {
+    let mut archive = ZipArchive::new(reader)?;
+    let name = match entry {
+        Some(name) => name.to_owned(),
+        None => {
+            let candidates = archive
+                .file_names()
+                .filter(|name| is_dat_name(name))
+                .collect::<Vec<_>>();
+            match candidates.as_slice() {
+                [name] => name.to_string(),
+                [] => {
+                    return Err(DatReaderError::ZipEntry(
+                        "No .dat or .xml entry found in zip archive".to_owned(),
+                    ))
+                }
+                _ => {
+                    return Err(DatReaderError::ZipEntry(format!(
+                        "Multiple candidate entries found in zip archive: {}",
+                        candidates.join(", ")
+                    )))
+                }
+            }
+        }
+    };
+    let mut file = archive.by_name(&name)?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).map_err(quick_xml::Error::from)?;
+    Ok(buf)
+}
+
+fn is_dat_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.ends_with(".dat") || name.ends_with(".xml")
+}
+
+#[test]
+fn test_zip_reader() {
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    let build = |names: &[&str]| {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            writer
+                .start_file(name.to_string(), SimpleFileOptions::default())
+                .unwrap();
+            writer
+                .write_all(crate::FULL_PARSE_INPUT.as_bytes())
+                .unwrap();
+        }
+        writer.finish().unwrap()
+    };
+    let expected = DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+
+    let zip = build(&["readme.txt", "Nintendo - Game Boy.dat"]);
+    let data_file = DatReader::from_zip_reader(zip, None)
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+
+    let zip = build(&["a.dat", "b.xml"]);
+    assert!(matches!(
+        DatReader::from_zip_reader(zip.clone(), None),
+        Err(DatReaderError::ZipEntry(ref msg)) if msg.contains("a.dat, b.xml")
+    ));
+    let data_file = DatReader::from_zip_reader(zip, Some("b.xml"))
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+
+    let zip = build(&["readme.txt"]);
+    assert!(matches!(
+        DatReader::from_zip_reader(zip.clone(), None),
+        Err(DatReaderError::ZipEntry(_))
+    ));
+    assert!(matches!(
+        DatReader::from_zip_reader(zip, Some("missing.dat")),
+        Err(DatReaderError::Zip(_))
+    ));
+}
@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::mem;
+use tokio::io::AsyncBufRead;
+
+use crate::{DatReader, DatReaderError, DatVisitor, DataFile, ParseWarning, VisitControl};
+
+impl<R: AsyncBufRead + Unpin> DatReader<R> {
+    /// Creates a reader for DAT files coming from an asynchronous source. All the options of
+    /// `DatReader` apply, and the input is parsed incrementally as it arrives.
+    pub fn from_async_reader(reader: R) -> DatReader<R> {
+        DatReader::from_xml_reader(quick_xml::Reader::from_reader(reader))
+    }
+    pub async fn read_all_async(mut self) -> Result<DataFile, DatReaderError> {
+        self.read_data_file_async(None).await
+    }
+    /// Like `read_all_async`, but also hands back the warnings collected while reading.
+    pub async fn read_all_with_warnings_async(
+        mut self,
+    ) -> Result<(DataFile, Vec<ParseWarning>), DatReaderError> {
+        let data_file = self.read_data_file_async(None).await?;
+        Ok((data_file, self.warnings))
+    }
+    /// Like `read_with_visitor`, but reads the input asynchronously.
+    pub async fn read_with_visitor_async<V: DatVisitor + Send>(
+        mut self,
+        visitor: &mut V,
+    ) -> Result<(), DatReaderError> {
+        self.read_data_file_async(Some(visitor)).await.map(|_| ())
+    }
+    async fn read_data_file_async(
+        &mut self,
+        mut visitor: Option<&mut (dyn DatVisitor + Send)>,
+    ) -> Result<DataFile, DatReaderError> {
+        let mut buf = mem::take(&mut self.buf);
+        let result = loop {
+            buf.clear();
+            let event = match self.reader.read_event_into_async(&mut buf).await {
+                Ok(event) => event,
+                Err(err) => break Err(err.into()),
+            };
+            match self.handle_event(event, visitor.as_mut().map(|visitor| &mut **visitor as _)) {
+                Ok(VisitControl::Continue) => (),
+                Ok(VisitControl::Stop) => break Ok(self.data_file.take().unwrap_or_default()),
+                Err(err) => break Err(err),
+            }
+        };
+        self.buf = buf;
+        result
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_async_read_all() {
+    let reader = DatReader::from_async_reader(crate::FULL_PARSE_INPUT.as_bytes());
+    let data_file = reader.read_all_async().await.unwrap();
+    let expected = DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+
+    let mut reader = DatReader::from_async_reader(&b"<datafile><foo/></datafile>"[..]);
+    reader.set_strict(false);
+    let (_, warnings) = reader.read_all_with_warnings_async().await.unwrap();
+    assert_eq!(warnings.len(), 1);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_async_options() {
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("child.dat"),
+        r#"<datafile><game name="Child"><rom name="b" size="2" crc="00000002"/></game></datafile>"#,
+    )
+    .unwrap();
+    let input = r#"<datafile>
+    <game name="First"><rom name="a" size="1" crc="00000001" extra="x"/></game>
+    <include href="child.dat"/>
+</datafile>"#;
+    let extras = Arc::new(Mutex::new(Vec::new()));
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let mut reader = DatReader::from_async_reader(input.as_bytes());
+    reader.set_include_dir(dir.path());
+    reader.register_rom_attr("extra", {
+        let extras = extras.clone();
+        move |_, value, index| extras.lock().unwrap().push((value.to_owned(), index))
+    });
+    reader.set_progress_callback({
+        let progress = progress.clone();
+        move |info| progress.lock().unwrap().push(info.games)
+    });
+    let data_file = reader.read_all_async().await.unwrap();
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| game.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["First", "Child"]);
+    assert_eq!(*extras.lock().unwrap(), [("x".to_owned(), 0)]);
+    assert_eq!(*progress.lock().unwrap(), [1, 2]);
+
+    let mut reader = DatReader::from_async_reader(&b"<datafile><game>"[..]);
+    reader.set_max_depth(1);
+    assert!(matches!(
+        reader.read_all_async().await,
+        Err(DatReaderError::MaxDepthExceeded(_))
+    ));
+}
+
+#[test]
+fn test_async_read_is_send() {
+    fn assert_send<T: Send>(_: T) {}
+    assert_send(DatReader::from_async_reader(&b""[..]).read_all_async());
+}
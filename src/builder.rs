@@ -0,0 +1,296 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{collections::HashSet, error::Error, fmt};
+
+use crate::{Crc32, DataFile, Disk, Game, Header, Md5, Release, Rom, Sha1, Status};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BuildError {
+    MissingField {
+        element: &'static str,
+        field: &'static str,
+    },
+    DuplicateGame(String),
+    DuplicateRom {
+        game: String,
+        rom: String,
+    },
+}
+
+impl Error for BuildError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::BuildError::*;
+        match self {
+            MissingField { element, field } => {
+                write!(f, "Missing required field \"{}\" in {}", field, element)
+            }
+            DuplicateGame(game) => write!(f, "Duplicate game name \"{}\"", game),
+            DuplicateRom { game, rom } => {
+                write!(f, "Duplicate rom name \"{}\" in game \"{}\"", rom, game)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DataFileBuilder {
+    data_file: DataFile,
+}
+
+impl DataFile {
+    pub fn builder() -> DataFileBuilder {
+        DataFileBuilder::default()
+    }
+}
+
+impl DataFileBuilder {
+    pub fn header(mut self, header: Header) -> DataFileBuilder {
+        self.data_file.header = Some(header);
+        self
+    }
+    pub fn game(mut self, game: Game) -> DataFileBuilder {
+        self.data_file.games.push(game);
+        self
+    }
+    pub fn build(self) -> Result<DataFile, BuildError> {
+        let mut names = HashSet::new();
+        for game in &self.data_file.games {
+            if !names.insert(game.name.as_str()) {
+                return Err(BuildError::DuplicateGame(game.name.clone()));
+            }
+        }
+        Ok(self.data_file)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameBuilder {
+    game: Game,
+}
+
+impl Game {
+    pub fn builder<S: Into<String>>(name: S) -> GameBuilder {
+        GameBuilder {
+            game: Game {
+                name: name.into(),
+                ..Game::default()
+            },
+        }
+    }
+}
+
+impl GameBuilder {
+    pub fn description<S: Into<String>>(mut self, description: S) -> GameBuilder {
+        self.game.description = description.into();
+        self
+    }
+    pub fn year<S: Into<String>>(mut self, year: S) -> GameBuilder {
+        self.game.year = year.into();
+        self
+    }
+    pub fn manufacturer<S: Into<String>>(mut self, manufacturer: S) -> GameBuilder {
+        self.game.manufacturer = manufacturer.into();
+        self
+    }
+    pub fn clone_of<S: Into<String>>(mut self, clone_of: S) -> GameBuilder {
+        self.game.clone_of = clone_of.into();
+        self
+    }
+    pub fn rom_of<S: Into<String>>(mut self, rom_of: S) -> GameBuilder {
+        self.game.rom_of = rom_of.into();
+        self
+    }
+    pub fn is_bios(mut self, is_bios: bool) -> GameBuilder {
+        self.game.is_bios = is_bios;
+        self
+    }
+    pub fn comment<S: Into<String>>(mut self, comment: S) -> GameBuilder {
+        self.game.comments.push(comment.into());
+        self
+    }
+    pub fn release(mut self, release: Release) -> GameBuilder {
+        self.game.releases.push(release);
+        self
+    }
+    pub fn rom(mut self, rom: Rom) -> GameBuilder {
+        self.game.roms.push(rom);
+        self
+    }
+    pub fn disk(mut self, disk: Disk) -> GameBuilder {
+        self.game.disks.push(disk);
+        self
+    }
+    pub fn build(self) -> Result<Game, BuildError> {
+        if self.game.name.is_empty() {
+            return Err(BuildError::MissingField {
+                element: "game",
+                field: "name",
+            });
+        }
+        let mut names = HashSet::new();
+        for rom in &self.game.roms {
+            if !names.insert(rom.name.as_str()) {
+                return Err(BuildError::DuplicateRom {
+                    game: self.game.name.clone(),
+                    rom: rom.name.clone(),
+                });
+            }
+        }
+        Ok(self.game)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RomBuilder {
+    rom: Rom,
+}
+
+impl Rom {
+    pub fn builder<S: Into<String>>(name: S) -> RomBuilder {
+        RomBuilder {
+            rom: Rom {
+                name: name.into(),
+                ..Rom::default()
+            },
+        }
+    }
+}
+
+impl RomBuilder {
+    pub fn size(mut self, size: u64) -> RomBuilder {
+        self.rom.size = Some(size);
+        self
+    }
+    pub fn crc(mut self, crc: Crc32) -> RomBuilder {
+        self.rom.crc = Some(crc);
+        self
+    }
+    pub fn sha1(mut self, sha1: Sha1) -> RomBuilder {
+        self.rom.sha1 = Some(sha1);
+        self
+    }
+    pub fn md5(mut self, md5: Md5) -> RomBuilder {
+        self.rom.md5 = Some(md5);
+        self
+    }
+    pub fn merge<S: Into<String>>(mut self, merge: S) -> RomBuilder {
+        self.rom.merge = merge.into();
+        self
+    }
+    pub fn status(mut self, status: Status) -> RomBuilder {
+        self.rom.status = status;
+        self
+    }
+    pub fn serial<S: Into<String>>(mut self, serial: S) -> RomBuilder {
+        self.rom.serial = serial.into();
+        self
+    }
+    /// Requires a name, and a size unless the status is `NoDump`.
+    pub fn build(self) -> Result<Rom, BuildError> {
+        if self.rom.name.is_empty() {
+            return Err(BuildError::MissingField {
+                element: "rom",
+                field: "name",
+            });
+        }
+        if self.rom.size.is_none() && self.rom.status != Status::NoDump {
+            return Err(BuildError::MissingField {
+                element: "rom",
+                field: "size",
+            });
+        }
+        Ok(self.rom)
+    }
+}
+
+#[test]
+fn test_builders() {
+    let data_file = DataFile::builder()
+        .header(Header {
+            name: "Name".to_owned(),
+            ..Header::default()
+        })
+        .game(
+            Game::builder("Game 1")
+                .description("Game 1 (World)")
+                .year("1990")
+                .rom(
+                    Rom::builder("Game 1.bin")
+                        .size(1024)
+                        .crc(Crc32(0x0123abcd))
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .game(
+            Game::builder("Game 2")
+                .clone_of("Game 1")
+                .rom(
+                    Rom::builder("Game 2.sav")
+                        .status(Status::NoDump)
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(
+        data_file,
+        DataFile {
+            header: Some(Header {
+                name: "Name".to_owned(),
+                ..Header::default()
+            }),
+            games: vec![
+                Game {
+                    name: "Game 1".to_owned(),
+                    description: "Game 1 (World)".to_owned(),
+                    year: "1990".to_owned(),
+                    roms: vec![Rom {
+                        name: "Game 1.bin".to_owned(),
+                        size: Some(1024),
+                        crc: Some(Crc32(0x0123abcd)),
+                        ..Rom::default()
+                    }],
+                    ..Game::default()
+                },
+                Game {
+                    name: "Game 2".to_owned(),
+                    clone_of: "Game 1".to_owned(),
+                    roms: vec![Rom {
+                        name: "Game 2.sav".to_owned(),
+                        status: Status::NoDump,
+                        ..Rom::default()
+                    }],
+                    ..Game::default()
+                },
+            ],
+            ..DataFile::default()
+        }
+    );
+
+    assert_eq!(
+        Rom::builder("x.bin").build(),
+        Err(BuildError::MissingField {
+            element: "rom",
+            field: "size"
+        })
+    );
+    assert!(matches!(
+        Game::builder("").build(),
+        Err(BuildError::MissingField { field: "name", .. })
+    ));
+    let game = Game::builder("X").build().unwrap();
+    assert_eq!(
+        DataFile::builder().game(game.clone()).game(game).build(),
+        Err(BuildError::DuplicateGame("X".to_owned()))
+    );
+}
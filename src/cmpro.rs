@@ -0,0 +1,380 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    str::CharIndices,
+};
+
+use crate::{DataFile, XmlCursor};
+
+pub struct CmproReader<B: BufRead> {
+    reader: B,
+    strict: bool,
+}
+
+impl CmproReader<&[u8]> {
+    pub fn from_string(text: &str) -> CmproReader<&[u8]> {
+        CmproReader::from_reader(text.as_bytes())
+    }
+}
+
+impl<B: BufRead> CmproReader<B> {
+    pub fn from_reader(reader: B) -> CmproReader<B> {
+        CmproReader {
+            reader,
+            strict: true,
+        }
+    }
+}
+
+impl CmproReader<BufReader<File>> {
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<CmproReader<BufReader<File>>, CmproReaderError> {
+        Ok(CmproReader::from_reader(BufReader::new(File::open(path)?)))
+    }
+}
+
+#[derive(Debug)]
+pub enum CmproReaderError {
+    Io(io::Error),
+    UnexpectedToken(String),
+    UnexpectedKey(String),
+    UnexpectedEof(String),
+}
+
+impl Error for CmproReaderError {}
+
+impl fmt::Display for CmproReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::CmproReaderError::*;
+        match self {
+            Io(err) => write!(f, "{}", err),
+            UnexpectedToken(msg) | UnexpectedKey(msg) | UnexpectedEof(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for CmproReaderError {
+    fn from(e: io::Error) -> CmproReaderError {
+        CmproReaderError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+struct Tokenizer<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, CmproReaderError> {
+        let (start, ch) = loop {
+            match self.chars.next() {
+                Some((_, ch)) if ch.is_whitespace() => (),
+                Some(next) => break next,
+                None => return Ok(None),
+            }
+        };
+        match ch {
+            '(' => Ok(Some(Token::Open)),
+            ')' => Ok(Some(Token::Close)),
+            '"' => loop {
+                match self.chars.next() {
+                    Some((end, '"')) => break Ok(Some(Token::Word(&self.text[start + 1..end]))),
+                    Some(_) => (),
+                    None => {
+                        break Err(CmproReaderError::UnexpectedEof(
+                            "Unexpected EOF while reading a quoted string".to_owned(),
+                        ))
+                    }
+                }
+            },
+            _ => {
+                let end = loop {
+                    match self.chars.clone().next() {
+                        Some((end, ch)) if ch.is_whitespace() || ch == '(' || ch == ')' => {
+                            break end
+                        }
+                        Some(_) => {
+                            self.chars.next();
+                        }
+                        None => break self.text.len(),
+                    }
+                };
+                Ok(Some(Token::Word(&self.text[start..end])))
+            }
+        }
+    }
+    fn expect_token(&mut self, context: &str) -> Result<Token<'a>, CmproReaderError> {
+        self.next_token()?.ok_or_else(|| {
+            CmproReaderError::UnexpectedEof(format!(
+                "Unexpected EOF while reading block \"{}\"",
+                context
+            ))
+        })
+    }
+}
+
+fn alias<'a>(tag: &str, key: &'a str) -> &'a str {
+    match (tag, key) {
+        ("datafile", "clrmamepro") => "header",
+        ("rom", "flags") | ("disk", "flags") => "status",
+        _ => key,
+    }
+}
+
+impl<B: BufRead> CmproReader<B> {
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+    pub fn read_all(mut self) -> Result<DataFile, CmproReaderError> {
+        let mut text = String::new();
+        self.reader.read_to_string(&mut text)?;
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let mut tokenizer = Tokenizer {
+            text,
+            chars: text.char_indices(),
+        };
+        let mut result = DataFile::default();
+        let cursor = XmlCursor {
+            tag: "datafile",
+            element: &mut result,
+        };
+        self.read_block(&mut tokenizer, cursor, true)?;
+        Ok(result)
+    }
+    fn read_block(
+        &self,
+        tokenizer: &mut Tokenizer,
+        mut cursor: XmlCursor,
+        top_level: bool,
+    ) -> Result<(), CmproReaderError> {
+        loop {
+            let key = match tokenizer.next_token()? {
+                Some(Token::Word(key)) => alias(cursor.tag, key),
+                Some(Token::Close) if !top_level => break Ok(()),
+                None if top_level => break Ok(()),
+                Some(token) => {
+                    break Err(CmproReaderError::UnexpectedToken(format!(
+                        "Unexpected token {:?} in block \"{}\"",
+                        token, cursor.tag
+                    )))
+                }
+                None => {
+                    break Err(CmproReaderError::UnexpectedEof(format!(
+                        "Unexpected EOF while reading block \"{}\"",
+                        cursor.tag
+                    )))
+                }
+            };
+            match tokenizer.expect_token(cursor.tag)? {
+                Token::Open => {
+                    if let Some(child) = cursor.element.child(key, true) {
+                        self.read_block(tokenizer, child, false)?;
+                    } else if self.strict {
+                        break Err(CmproReaderError::UnexpectedKey(format!(
+                            "Unexpected block \"{}\" in block \"{}\"",
+                            key, cursor.tag
+                        )));
+                    } else {
+                        skip_block(tokenizer, key)?;
+                    }
+                }
+                Token::Word(value) if !top_level => {
+                    if !self.apply_value(&mut cursor, key, value) && self.strict {
+                        break Err(CmproReaderError::UnexpectedKey(format!(
+                            "Unexpected value {}=\"{}\" in block \"{}\"",
+                            key, value, cursor.tag
+                        )));
+                    }
+                }
+                token => {
+                    break Err(CmproReaderError::UnexpectedToken(format!(
+                        "Unexpected token {:?} after \"{}\" in block \"{}\"",
+                        token, key, cursor.tag
+                    )))
+                }
+            }
+        }
+    }
+    fn apply_value(&self, cursor: &mut XmlCursor, key: &str, value: &str) -> bool {
+        let element = &mut *cursor.element;
+        if let Some(target) = element.attr(key) {
+            return target.set_from_str(value);
+        }
+        if cursor.tag == "header" {
+            if let "header" | "forcemerging" | "forcenodump" | "forcepacking" = key {
+                return element
+                    .child("clrmamepro", true)
+                    .and_then(|child| {
+                        child
+                            .element
+                            .attr(key)
+                            .map(|target| target.set_from_str(value))
+                    })
+                    .unwrap_or(false);
+            }
+        }
+        match element.child(key, true) {
+            Some(child) => match child.element.content() {
+                Some(content) => {
+                    content.push_str(value);
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+}
+
+fn skip_block(tokenizer: &mut Tokenizer, context: &str) -> Result<(), CmproReaderError> {
+    let mut level = 1;
+    while level > 0 {
+        match tokenizer.expect_token(context)? {
+            Token::Open => level += 1,
+            Token::Close => level -= 1,
+            Token::Word(_) => (),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tokenizer() {
+    let text = r#"game ( name "Some Game" rom(name x.bin size 1024) )"#;
+    let mut tokenizer = Tokenizer {
+        text,
+        chars: text.char_indices(),
+    };
+    let mut tokens = Vec::new();
+    while let Some(token) = tokenizer.next_token().unwrap() {
+        tokens.push(token);
+    }
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Word("game"),
+            Token::Open,
+            Token::Word("name"),
+            Token::Word("Some Game"),
+            Token::Word("rom"),
+            Token::Open,
+            Token::Word("name"),
+            Token::Word("x.bin"),
+            Token::Word("size"),
+            Token::Word("1024"),
+            Token::Close,
+            Token::Close,
+        ]
+    );
+}
+
+#[test]
+fn test_cmpro_parse() {
+    let cmpro = r#"
+clrmamepro (
+	name "Name"
+	description "Description"
+	version 20220101
+	author "Author"
+	forcemerging full
+)
+
+game (
+	name "Game 1"
+	description "Game 1 (World)"
+	year 1990
+	manufacturer "Manufacturer"
+	rom ( name "Game 1.bin" size 1024 crc 0123abcd md5 d41d8cd98f00b204e9800998ecf8427e sha1 da39a3ee5e6b4b0d3255bfef95601890afd80709 )
+	rom ( name "Game 1.sav" size 32768 flags nodump )
+)
+
+game (
+	name "Game 2"
+	cloneof "Game 1"
+	description "Game 2"
+	disk ( name "Game 2" sha1 da39a3ee5e6b4b0d3255bfef95601890afd80709 )
+)
+"#;
+    let xml = r#"
+<?xml version="1.0"?>
+<datafile>
+    <header>
+        <name>Name</name>
+        <description>Description</description>
+        <version>20220101</version>
+        <author>Author</author>
+        <clrmamepro forcemerging="full"/>
+    </header>
+    <game name="Game 1">
+        <description>Game 1 (World)</description>
+        <year>1990</year>
+        <manufacturer>Manufacturer</manufacturer>
+        <rom name="Game 1.bin" size="1024" crc="0123abcd" md5="d41d8cd98f00b204e9800998ecf8427e" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709"/>
+        <rom name="Game 1.sav" size="32768" status="nodump"/>
+    </game>
+    <game name="Game 2" cloneof="Game 1">
+        <description>Game 2</description>
+        <disk name="Game 2" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709"/>
+    </game>
+</datafile>"#;
+    let from_cmpro = CmproReader::from_string(cmpro).read_all().unwrap();
+    let from_xml = crate::DatReader::from_string(xml).read_all().unwrap();
+    assert_eq!(from_cmpro, from_xml);
+    let with_bom = format!("\u{feff}{}", cmpro.trim_start());
+    assert_eq!(
+        CmproReader::from_string(&with_bom).read_all().unwrap(),
+        from_xml
+    );
+}
+
+#[test]
+fn test_cmpro_errors() {
+    let read = |text: &str, strict: bool| {
+        let mut reader = CmproReader::from_string(text);
+        reader.set_strict(strict);
+        reader.read_all()
+    };
+    assert!(matches!(
+        read(r#"game ( name "x" )"#, true),
+        Ok(ref data_file) if data_file.games.len() == 1
+    ));
+    assert!(matches!(
+        read(r#"game ( name "x"#, true),
+        Err(CmproReaderError::UnexpectedEof(_))
+    ));
+    assert!(matches!(
+        read(r#"game ( name x"#, true),
+        Err(CmproReaderError::UnexpectedEof(_))
+    ));
+    assert!(matches!(
+        read(r#"game ( name x ) )"#, true),
+        Err(CmproReaderError::UnexpectedToken(_))
+    ));
+    assert!(matches!(
+        read(r#"game ( name x video ( screen raster ) )"#, true),
+        Err(CmproReaderError::UnexpectedKey(_))
+    ));
+    let data_file = read(
+        r#"emulator ( name x ) game ( name x video ( screen raster ) )"#,
+        false,
+    )
+    .unwrap();
+    assert_eq!(data_file.games[0].name, "x");
+}
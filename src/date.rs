@@ -0,0 +1,126 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::string::String;
+use core::fmt;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatDate {
+    pub raw: String,
+    pub parsed: Option<Date>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl DatDate {
+    pub fn new<S: Into<String>>(raw: S) -> DatDate {
+        let raw = raw.into();
+        let parsed = Date::parse(&raw);
+        DatDate { raw, parsed }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+    pub fn year(&self) -> Option<u16> {
+        self.parsed.map(|date| date.year)
+    }
+    pub fn month(&self) -> Option<u8> {
+        self.parsed.map(|date| date.month)
+    }
+    pub fn day(&self) -> Option<u8> {
+        self.parsed.map(|date| date.day)
+    }
+}
+
+impl Date {
+    fn parse(value: &str) -> Option<Date> {
+        let value = value.trim();
+        // The slicing below assumes one byte per character
+        if !value.is_ascii() {
+            return None;
+        }
+        let (year, month, day) = match value.len() {
+            8 => (&value[0..4], &value[4..6], &value[6..8]),
+            10 if &value[4..5] == "-" && &value[7..8] == "-" => {
+                (&value[0..4], &value[5..7], &value[8..10])
+            }
+            _ => return None,
+        };
+        let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if !digits(year) || !digits(month) || !digits(day) {
+            return None;
+        }
+        let date = Date {
+            year: year.parse().ok()?,
+            month: month.parse().ok()?,
+            day: day.parse().ok()?,
+        };
+        if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) {
+            Some(date)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for DatDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DatDate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DatDate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<DatDate, D::Error> {
+        String::deserialize(deserializer).map(DatDate::new)
+    }
+}
+
+#[test]
+fn test_dat_date() {
+    let date = DatDate::new("20220131");
+    assert_eq!(
+        date.parsed,
+        Some(Date {
+            year: 2022,
+            month: 1,
+            day: 31
+        })
+    );
+    let date = DatDate::new("1996-02-27");
+    assert_eq!(
+        (date.year(), date.month(), date.day()),
+        (Some(1996), Some(2), Some(27))
+    );
+    assert_eq!(date.raw, "1996-02-27");
+    assert_eq!(DatDate::new("19960227").parsed, date.parsed);
+
+    let date = DatDate::new("Spring 1996");
+    assert_eq!(date.parsed, None);
+    assert_eq!(date.raw, "Spring 1996");
+    assert_eq!(DatDate::new("1996-13-01").parsed, None);
+    assert_eq!(DatDate::new("1996/02/27").parsed, None);
+    assert_eq!(DatDate::new("+1996027").parsed, None);
+    assert_eq!(DatDate::new("123é567").parsed, None);
+    assert_eq!(DatDate::new("199é02-27").parsed, None);
+}
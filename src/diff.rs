@@ -0,0 +1,207 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+
+use crate::{DataFile, Game, Rom};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatDiff<'a> {
+    pub added: Vec<&'a Game>,
+    pub removed: Vec<&'a Game>,
+    pub changed: Vec<GameDiff<'a>>,
+}
+
+impl<'a> DatDiff<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Rom changes within a game, comparing roms by name, CRC, and size
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameDiff<'a> {
+    pub old: &'a Game,
+    pub new: &'a Game,
+    pub added_roms: Vec<&'a Rom>,
+    pub removed_roms: Vec<&'a Rom>,
+    pub modified_roms: Vec<(&'a Rom, &'a Rom)>,
+}
+
+impl<'a> GameDiff<'a> {
+    fn new(old: &'a Game, new: &'a Game) -> GameDiff<'a> {
+        let old_roms = index_by_name(&old.roms);
+        let new_roms = index_by_name(&new.roms);
+        let mut diff = GameDiff {
+            old,
+            new,
+            added_roms: Vec::new(),
+            removed_roms: Vec::new(),
+            modified_roms: Vec::new(),
+        };
+        for rom in &old.roms {
+            match new_roms.get(rom.name.as_str()) {
+                Some(&new_rom) if (rom.crc, rom.size) != (new_rom.crc, new_rom.size) => {
+                    diff.modified_roms.push((rom, new_rom))
+                }
+                Some(_) => (),
+                None => diff.removed_roms.push(rom),
+            }
+        }
+        for rom in &new.roms {
+            if !old_roms.contains_key(rom.name.as_str()) {
+                diff.added_roms.push(rom);
+            }
+        }
+        diff
+    }
+    pub fn is_empty(&self) -> bool {
+        self.added_roms.is_empty() && self.removed_roms.is_empty() && self.modified_roms.is_empty()
+    }
+}
+
+impl Game {
+    /// Compares name, roms, and disks, ignoring the order of roms and disks. Other game
+    /// metadata is not compared.
+    pub fn semantically_eq(&self, other: &Game) -> bool {
+        self.name == other.name
+            && same_multiset(&self.roms, &other.roms)
+            && same_multiset(&self.disks, &other.disks)
+    }
+}
+
+fn same_multiset<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut matched = vec![false; b.len()];
+    a.iter().all(
+        |x| match (0..b.len()).find(|&i| !matched[i] && b[i] == *x) {
+            Some(i) => {
+                matched[i] = true;
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+fn index_by_name(roms: &[Rom]) -> HashMap<&str, &Rom> {
+    let mut index = HashMap::new();
+    for rom in roms {
+        index.entry(rom.name.as_str()).or_insert(rom);
+    }
+    index
+}
+
+fn index_games(games: &[Game]) -> HashMap<&str, &Game> {
+    let mut index = HashMap::new();
+    for game in games {
+        index.entry(game.name.as_str()).or_insert(game);
+    }
+    index
+}
+
+impl DataFile {
+    /// Compares the games of this file (old) against `other` (new), keyed by game name
+    pub fn diff<'a>(&'a self, other: &'a DataFile) -> DatDiff<'a> {
+        let old_games = index_games(&self.games);
+        let new_games = index_games(&other.games);
+        let mut diff = DatDiff::default();
+        for game in &self.games {
+            match new_games.get(game.name.as_str()) {
+                Some(&new_game) => {
+                    let game_diff = GameDiff::new(game, new_game);
+                    if !game_diff.is_empty() {
+                        diff.changed.push(game_diff);
+                    }
+                }
+                None => diff.removed.push(game),
+            }
+        }
+        for game in &other.games {
+            if !old_games.contains_key(game.name.as_str()) {
+                diff.added.push(game);
+            }
+        }
+        diff
+    }
+}
+
+#[test]
+fn test_diff() {
+    let old = crate::DatReader::from_string(
+        r#"
+<datafile>
+    <game name="A">
+        <rom name="a1" size="16" crc="00000001"/>
+        <rom name="a2" size="16" crc="00000002"/>
+    </game>
+    <game name="B">
+        <rom name="b1" size="16" crc="00000003"/>
+    </game>
+    <game name="C">
+        <rom name="c1" size="16" crc="00000004"/>
+    </game>
+</datafile>"#,
+    )
+    .read_all()
+    .unwrap();
+    let new = crate::DatReader::from_string(
+        r#"
+<datafile>
+    <game name="A">
+        <rom name="a1" size="16" crc="00000001"/>
+        <rom name="a2" size="32" crc="00000002"/>
+        <rom name="a3" size="16" crc="00000005"/>
+    </game>
+    <game name="C">
+        <rom name="c1" size="16" crc="00000004"/>
+    </game>
+    <game name="D">
+        <rom name="d1" size="16" crc="00000006"/>
+    </game>
+</datafile>"#,
+    )
+    .read_all()
+    .unwrap();
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, vec![&new.games[2]]);
+    assert_eq!(diff.removed, vec![&old.games[1]]);
+    assert_eq!(
+        diff.changed,
+        vec![GameDiff {
+            old: &old.games[0],
+            new: &new.games[0],
+            added_roms: vec![&new.games[0].roms[2]],
+            removed_roms: vec![],
+            modified_roms: vec![(&old.games[0].roms[1], &new.games[0].roms[1])],
+        }]
+    );
+    assert!(old.diff(&old).is_empty());
+}
+
+#[test]
+fn test_semantically_eq() {
+    let rom = |name: &str, size: u64| Rom {
+        name: name.to_owned(),
+        size: Some(size),
+        ..Rom::default()
+    };
+    let game = |roms: Vec<Rom>| Game {
+        name: "Game".to_owned(),
+        roms,
+        ..Game::default()
+    };
+    let a = game(vec![rom("a", 1), rom("b", 2), rom("a", 1)]);
+    let b = game(vec![rom("b", 2), rom("a", 1), rom("a", 1)]);
+    assert_ne!(a, b);
+    assert!(a.semantically_eq(&b));
+    assert!(!a.semantically_eq(&game(vec![rom("b", 2), rom("b", 2), rom("a", 1)])));
+    assert!(!a.semantically_eq(&game(vec![rom("a", 1), rom("b", 2)])));
+    assert!(!a.semantically_eq(&Game {
+        name: "Other".to_owned(),
+        ..a.clone()
+    }));
+}
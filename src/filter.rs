@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashSet;
+
+use crate::{DataFile, Game, Rom};
+
+impl DataFile {
+    /// Keeps only the games for which `f` returns true, preserving their order
+    pub fn retain_games<F: FnMut(&Game) -> bool>(&mut self, f: F) {
+        self.games.retain(f);
+    }
+    /// Keeps only the roms for which `f` returns true. Games left without roms are kept.
+    pub fn retain_roms<F: FnMut(&Rom) -> bool>(&mut self, mut f: F) {
+        for game in &mut self.games {
+            game.roms.retain(&mut f);
+        }
+    }
+    /// Removes duplicate roms from every game. Returns the number of roms removed.
+    pub fn dedup_roms(&mut self) -> usize {
+        self.games.iter_mut().map(Game::dedup_roms).sum()
+    }
+}
+
+impl Game {
+    /// Removes roms equal in name, size, and hashes to an earlier rom, preserving order.
+    /// Returns the number of roms removed.
+    pub fn dedup_roms(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        let keep = self
+            .roms
+            .iter()
+            .map(|rom| seen.insert((&rom.name, rom.size, rom.crc, rom.sha1, rom.sha256, rom.md5)))
+            .collect::<Vec<_>>();
+        let len = self.roms.len();
+        let mut keep = keep.into_iter();
+        self.roms.retain(|_| keep.next().unwrap_or(true));
+        len - self.roms.len()
+    }
+}
+
+#[test]
+fn test_retain() {
+    let game = |name: &str| {
+        Game::builder(name)
+            .rom(
+                Rom::builder(format!("{}.bin", name))
+                    .size(1)
+                    .build()
+                    .unwrap(),
+            )
+            .rom(
+                Rom::builder(format!("{}.sav", name))
+                    .size(2)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    };
+    let mut data_file = DataFile::builder()
+        .game(game("Game 1"))
+        .game(game("Demo 1"))
+        .game(game("Game 2"))
+        .build()
+        .unwrap();
+    data_file.retain_games(|game| game.name.starts_with("Game"));
+    data_file.retain_roms(|rom| rom.name.ends_with(".bin"));
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| (game.name.as_str(), game.roms.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("Game 1", 1), ("Game 2", 1)]);
+}
+
+#[test]
+fn test_dedup_roms() {
+    let rom = |name: &str, size: u64| Rom::builder(name).size(size).build().unwrap();
+    // The builder rejects duplicate roms, so build the game directly
+    let mut data_file = DataFile {
+        games: vec![Game {
+            name: "Game".to_owned(),
+            roms: vec![
+                rom("a.bin", 1),
+                rom("b.bin", 1),
+                rom("a.bin", 1),
+                rom("a.bin", 2),
+            ],
+            ..Game::default()
+        }],
+        ..DataFile::default()
+    };
+    assert_eq!(data_file.dedup_roms(), 1);
+    let roms = data_file.games[0]
+        .roms
+        .iter()
+        .map(|rom| (rom.name.as_str(), rom.size))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roms,
+        [("a.bin", Some(1)), ("b.bin", Some(1)), ("a.bin", Some(2))]
+    );
+    assert_eq!(data_file.dedup_roms(), 0);
+}
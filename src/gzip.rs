@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use flate2::bufread::GzDecoder;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{open::GZIP_MAGIC, DatReader, DatReaderError};
+
+impl DatReader<Box<dyn BufRead + Send>> {
+    /// Opens a possibly gzip-compressed file. Plain XML files are read as-is.
+    pub fn from_gzip_file<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+        let file = File::open(path).map_err(quick_xml::Error::from)?;
+        DatReader::from_gzip_reader(BufReader::new(file))
+    }
+    pub fn from_gzip_reader<R: BufRead + Send + 'static>(
+        mut reader: R,
+    ) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+        let buf = reader.fill_buf().map_err(quick_xml::Error::from)?;
+        let reader: Box<dyn BufRead + Send> = if buf.starts_with(&GZIP_MAGIC) {
+            Box::new(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+        Ok(DatReader::from_reader(reader))
+    }
+}
+
+#[test]
+fn test_gzip_reader() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::{Cursor, Write};
+
+    let expected = DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(crate::FULL_PARSE_INPUT.as_bytes())
+        .unwrap();
+    let compressed = encoder.finish().unwrap();
+    let data_file = DatReader::from_gzip_reader(Cursor::new(compressed))
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+
+    let plain = Cursor::new(crate::FULL_PARSE_INPUT.as_bytes().to_vec());
+    let data_file = DatReader::from_gzip_reader(plain)
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+}
@@ -0,0 +1,209 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::fmt;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Crc32(pub u32);
+
+impl Crc32 {
+    pub(crate) fn parse(value: &str) -> Option<Crc32> {
+        let value = value.trim();
+        let hex = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u32::from_str_radix(hex, 16).ok().map(Crc32)
+    }
+}
+
+impl fmt::Display for Crc32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:08x}", self.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Sha1(pub [u8; 20]);
+
+impl Sha1 {
+    pub(crate) fn parse(value: &str) -> Option<Sha1> {
+        parse_hex(value).map(Sha1)
+    }
+}
+
+impl fmt::Display for Sha1 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Md5(pub [u8; 16]);
+
+impl Md5 {
+    pub(crate) fn parse(value: &str) -> Option<Md5> {
+        parse_hex(value).map(Md5)
+    }
+}
+
+impl fmt::Display for Md5 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Sha256(pub [u8; 32]);
+
+impl Sha256 {
+    pub(crate) fn parse(value: &str) -> Option<Sha256> {
+        parse_hex(value).map(Sha256)
+    }
+}
+
+impl fmt::Display for Sha256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_hex {
+    ($ty:ident) => {
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+                let value = <alloc::borrow::Cow<str>>::deserialize(deserializer)?;
+                $ty::parse(&value).ok_or_else(|| {
+                    serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Str(&value),
+                        &concat!("a hex-encoded ", stringify!($ty)),
+                    )
+                })
+            }
+        }
+    };
+}
+
+/// A borrowed hash of any supported algorithm
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HashRef<'a> {
+    Crc32(&'a Crc32),
+    Sha1(&'a Sha1),
+    Sha256(&'a Sha256),
+    Md5(&'a Md5),
+}
+
+impl<'a> fmt::Display for HashRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashRef::Crc32(crc) => write!(f, "crc32:{}", crc),
+            HashRef::Sha1(sha1) => write!(f, "sha1:{}", sha1),
+            HashRef::Sha256(sha256) => write!(f, "sha256:{}", sha256),
+            HashRef::Md5(md5) => write!(f, "md5:{}", md5),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_hex!(Crc32);
+#[cfg(feature = "serde")]
+impl_serde_hex!(Sha1);
+#[cfg(feature = "serde")]
+impl_serde_hex!(Md5);
+#[cfg(feature = "serde")]
+impl_serde_hex!(Sha256);
+
+fn parse_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    let value = value.trim().as_bytes();
+    if value.len() != N * 2 {
+        return None;
+    }
+    let mut result = [0; N];
+    for (byte, digits) in result.iter_mut().zip(value.chunks_exact(2)) {
+        let hi = (digits[0] as char).to_digit(16)?;
+        let lo = (digits[1] as char).to_digit(16)?;
+        *byte = (hi << 4 | lo) as u8;
+    }
+    Some(result)
+}
+
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+use alloc::string::ToString;
+
+#[test]
+fn test_crc32_parse() {
+    assert_eq!(Crc32::parse("deadbeef"), Some(Crc32(0xdeadbeef)));
+    assert_eq!(Crc32::parse("DEADBEEF"), Some(Crc32(0xdeadbeef)));
+    assert_eq!(Crc32::parse("0x0000ABCD"), Some(Crc32(0xabcd)));
+    assert_eq!(Crc32::parse(" deadbeef\n"), Some(Crc32(0xdeadbeef)));
+    assert_eq!(Crc32::parse("\t0xdeadbeef "), Some(Crc32(0xdeadbeef)));
+    assert_eq!(Crc32::parse("dead beef"), None);
+    assert_eq!(Crc32::parse("abcdef"), None);
+    assert_eq!(Crc32::parse("0123456789"), None);
+    assert_eq!(Crc32::parse("+1234567"), None);
+    assert_eq!(Crc32::parse("XYZ"), None);
+}
+
+#[test]
+fn test_crc32_display() {
+    assert_eq!(Crc32(0xabcd).to_string(), "0000abcd");
+}
+
+#[test]
+fn test_sha1_parse() {
+    let sha1 = Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap();
+    assert_eq!(sha1.0[0], 0xda);
+    assert_eq!(sha1.0[19], 0x09);
+    assert_eq!(
+        Sha1::parse(" DA39A3EE5e6b4b0d3255bfef95601890afd80709\n"),
+        Some(sha1)
+    );
+    assert_eq!(sha1.to_string(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd807"), None);
+    assert_eq!(
+        Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd8070x"),
+        None
+    );
+}
+
+#[test]
+fn test_md5_parse() {
+    let md5 = Md5::parse("D41D8CD98F00B204E9800998ECF8427E").unwrap();
+    assert_eq!(md5.to_string(), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(Md5::parse("\td41d8cd98f00b204e9800998ecf8427e "), Some(md5));
+    assert_eq!(Md5::parse("d41d8cd98f00b204e9800998ecf8427"), None);
+    assert_eq!(Md5::parse("d41d8cd98f00b204 e9800998ecf8427e"), None);
+}
+
+#[test]
+fn test_sha256_parse() {
+    let sha256 =
+        Sha256::parse("E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855").unwrap();
+    assert_eq!(
+        sha256.to_string(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        Sha256::parse("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b8"),
+        None
+    );
+    assert_eq!(Sha256::parse("Sha256"), None);
+}
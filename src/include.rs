@@ -0,0 +1,201 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+use crate::{DatReader, DatReaderError, Game};
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+impl<B> DatReader<B> {
+    /// Resolves `<include href="..."/>` elements in the root element relative to `dir`, and
+    /// splices in the games of the included file at that point. Paths in included files are
+    /// resolved relative to the including file, and must not point outside of `dir`.
+    ///
+    /// Included files are read with blocking I/O, also when reading asynchronously.
+    pub fn set_include_dir<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.include_dir = Some(dir.into());
+    }
+    pub(crate) fn read_include(
+        &mut self,
+        href: Option<String>,
+    ) -> Result<Vec<Game>, DatReaderError> {
+        let href = href.ok_or_else(|| {
+            DatReaderError::Include("Missing attribute \"href\" in element \"include\"".to_owned())
+        })?;
+        if Path::new(&href).is_absolute() {
+            return Err(DatReaderError::Include(format!(
+                "Absolute include path \"{}\" is not allowed",
+                href
+            )));
+        }
+        let dir = self.include_dir.as_deref().unwrap_or_else(|| Path::new(""));
+        let root = match &self.include_root {
+            Some(root) => root.clone(),
+            None => canonicalize(dir)?,
+        };
+        let path = canonicalize(&dir.join(&href))?;
+        if !path.starts_with(&root) {
+            return Err(DatReaderError::Include(format!(
+                "Include \"{}\" is outside of the include directory \"{}\"",
+                href,
+                root.display()
+            )));
+        }
+        if self.include_stack.contains(&path) {
+            return Err(DatReaderError::Include(format!(
+                "Include cycle at \"{}\"",
+                path.display()
+            )));
+        }
+        if self.include_stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(DatReaderError::Include(format!(
+                "Includes nested deeper than {} levels at \"{}\"",
+                MAX_INCLUDE_DEPTH,
+                path.display()
+            )));
+        }
+        let mut reader = DatReader::from_file(&path)?;
+        reader.options = self.options;
+        reader.include_dir = path.parent().map(Path::to_path_buf);
+        reader.include_root = Some(root);
+        reader.include_stack = self.include_stack.clone();
+        reader.include_stack.push(path);
+        let (data_file, warnings) = reader.read_all_with_warnings()?;
+        self.warnings.extend(warnings);
+        Ok(data_file.games)
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, DatReaderError> {
+    path.canonicalize().map_err(|err| {
+        DatReaderError::Include(format!(
+            "Failed to resolve include \"{}\": {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+#[test]
+fn test_include() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(
+        dir.path().join("sub/child.dat"),
+        r#"<datafile><game name="Child"/><include href="grandchild.dat"/></datafile>"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("sub/grandchild.dat"),
+        r#"<datafile><game name="Grandchild"/></datafile>"#,
+    )
+    .unwrap();
+    let input = r#"
+<datafile>
+    <game name="First"/>
+    <include href="sub/child.dat"/>
+    <game name="Last"/>
+</datafile>"#;
+    let mut reader = DatReader::from_string(input);
+    reader.set_include_dir(dir.path());
+    let names = reader
+        .read_all()
+        .unwrap()
+        .games
+        .into_iter()
+        .map(|game| game.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["First", "Child", "Grandchild", "Last"]);
+
+    // Without an include directory, <include> is just an unknown element
+    assert!(DatReader::from_string(input).read_all().is_err());
+}
+
+#[test]
+fn test_include_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("a.dat"),
+        r#"<datafile><include href="b.dat"/></datafile>"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("b.dat"),
+        r#"<datafile><include href="a.dat"/></datafile>"#,
+    )
+    .unwrap();
+    let mut reader = DatReader::from_string(r#"<datafile><include href="a.dat"/></datafile>"#);
+    reader.set_include_dir(dir.path());
+    assert!(matches!(
+        reader.read_all(),
+        Err(DatReaderError::Include(msg)) if msg.starts_with("Include cycle")
+    ));
+}
+
+#[test]
+fn test_include_outside_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(
+        dir.path().join("outside.dat"),
+        r#"<datafile><game name="Outside"/></datafile>"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("sub/escape.dat"),
+        r#"<datafile><include href="../outside.dat"/></datafile>"#,
+    )
+    .unwrap();
+    let read = |input: &str| {
+        let mut reader = DatReader::from_string(input);
+        reader.set_include_dir(dir.path().join("sub"));
+        reader.read_all()
+    };
+    assert!(matches!(
+        read(r#"<datafile><include href="../outside.dat"/></datafile>"#),
+        Err(DatReaderError::Include(msg)) if msg.contains("outside of the include directory")
+    ));
+    assert!(matches!(
+        read(r#"<datafile><include href="escape.dat"/></datafile>"#),
+        Err(DatReaderError::Include(msg)) if msg.contains("outside of the include directory")
+    ));
+    let absolute = format!(
+        r#"<datafile><include href="{}"/></datafile>"#,
+        dir.path().join("outside.dat").display()
+    );
+    assert!(matches!(
+        read(&absolute),
+        Err(DatReaderError::Include(msg)) if msg.starts_with("Absolute include path")
+    ));
+}
+
+#[test]
+fn test_include_bookkeeping() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("child.dat"),
+        r#"<datafile><game name="Child"/><game name="First"/></datafile>"#,
+    )
+    .unwrap();
+    let input = r#"<datafile><game name="First"/><include href="child.dat"/></datafile>"#;
+
+    let mut reader = DatReader::from_string(input);
+    reader.set_include_dir(dir.path());
+    reader.set_reject_duplicates(true);
+    assert!(matches!(
+        reader.read_all(),
+        Err(DatReaderError::DuplicateName(msg)) if msg.contains("\"First\"")
+    ));
+
+    let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut reader = DatReader::from_string(input);
+    reader.set_include_dir(dir.path());
+    reader.set_progress_callback({
+        let progress = progress.clone();
+        move |info| progress.lock().unwrap().push(info.games)
+    });
+    assert_eq!(reader.read_all().unwrap().games.len(), 3);
+    assert_eq!(*progress.lock().unwrap(), [1, 2, 3]);
+}
@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io::{self, Write};
+
+use crate::DataFile;
+
+impl DataFile {
+    /// Writes the games as JSON Lines: one JSON object per game, each on its own line.
+    /// The header and other datafile-level fields are not included.
+    pub fn write_jsonl<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for game in &self.games {
+            serde_json::to_writer(&mut w, game)?;
+            w.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_jsonl() {
+    let data_file = crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    let mut output = Vec::new();
+    data_file.write_jsonl(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), data_file.games.len());
+    for (line, game) in lines.iter().zip(&data_file.games) {
+        let parsed: crate::Game = serde_json::from_str(line).unwrap();
+        assert_eq!(&parsed, game);
+    }
+
+    let mut output = Vec::new();
+    DataFile::default().write_jsonl(&mut output).unwrap();
+    assert!(output.is_empty());
+}
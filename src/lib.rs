@@ -2,231 +2,246 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use quick_xml::events::{attributes::Attributes, Event};
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use quick_xml::{
+    encoding::Decoder,
+    events::{
+        attributes::{AttrError, Attributes},
+        BytesStart, Event,
+    },
+};
+#[cfg(feature = "std")]
 use std::{
-    borrow::Borrow,
+    collections::{HashMap, HashSet},
     error::Error,
     fmt,
     fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
+    io::{BufRead, BufReader, Write},
+    mem,
+    path::{Path, PathBuf},
 };
 
-use crate::xml_element::XmlElement;
+#[cfg(feature = "std")]
+use crate::{xml_element::XmlElement, xml_write::XmlWriter};
+
+#[cfg(all(feature = "std", feature = "rayon"))]
+pub use crate::parallel::read_dir_parallel;
+#[cfg(feature = "hashing")]
+pub use crate::verify::VerifyResult;
+#[cfg(feature = "std")]
+pub use crate::{
+    builder::{BuildError, DataFileBuilder, GameBuilder, RomBuilder},
+    cmpro::{CmproReader, CmproReaderError},
+    diff::{DatDiff, GameDiff},
+    lookup::{CloneChainError, RomIndex, RomOrDisk},
+    merge::{GameConflict, HeaderMerge, MergeError, MergeStrategy, MergeSummary},
+    record::RomRecord,
+    sort::RomOrder,
+    stats::DatStats,
+    validate::ValidationError,
+    visitor::{DatVisitor, VisitControl},
+};
+pub use crate::{
+    date::{DatDate, Date},
+    hash::{Crc32, HashRef, Md5, Sha1, Sha256},
+    model::{
+        Archive, BiosSet, ClrMamePro, DataFile, DataFileElementKind, DeviceRef, Disk, Driver,
+        DriverStatus, ForceMerging, ForceNoDump, ForcePacking, Game, GameElementKind, Header,
+        Release, Rom, RomCenter, RomMode, Sample, SampleMode, SaveState, Status, UnknownContent,
+        UnknownElement,
+    },
+    xml_attr::ParseEnumError,
+};
 
+// Only the data model is available without the "std" feature
+#[cfg(all(feature = "std", feature = "zip"))]
+mod archive;
+#[cfg(all(feature = "std", feature = "tokio"))]
+mod async_reader;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod cmpro;
+mod date;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(all(feature = "std", feature = "flate2"))]
+mod gzip;
+mod hash;
+#[cfg(feature = "std")]
+mod include;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "std")]
+mod lookup;
+#[cfg(feature = "std")]
+mod merge;
+mod model;
+#[cfg(feature = "std")]
+mod open;
+#[cfg(all(feature = "std", feature = "rayon"))]
+mod parallel;
+#[cfg(feature = "std")]
+mod record;
+#[cfg(feature = "std")]
+mod region;
+#[cfg(feature = "std")]
+mod sort;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "hashing")]
+mod verify;
+#[cfg(feature = "std")]
+mod visitor;
 mod xml_attr;
+#[cfg(feature = "std")]
 mod xml_element;
+#[cfg(feature = "std")]
+mod xml_write;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct DataFile {
-    pub build: String,
-    pub debug: bool,
-    pub header: Option<Header>,
-    pub games: Vec<Game>,
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Header {
-    pub name: String,
-    pub description: String,
-    pub category: String,
-    pub version: String,
-    pub date: String,
-    pub author: String,
-    pub email: String,
-    pub homepage: String,
-    pub url: String,
-    pub comment: String,
-    pub clr_mame_pro: Option<ClrMamePro>,
-    pub rom_center: Option<RomCenter>,
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct ClrMamePro {
-    pub header: String,
-    pub force_merging: ForceMerging,
-    pub force_no_dump: ForceNoDump,
-    pub force_packing: ForcePacking,
-}
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum ForceMerging {
-    None,
-    Split,
-    Full,
-}
-
-impl Default for ForceMerging {
-    fn default() -> ForceMerging {
-        ForceMerging::Split
-    }
-}
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum ForceNoDump {
-    Obsolete,
-    Required,
-    Ignore,
-}
-
-impl Default for ForceNoDump {
-    fn default() -> ForceNoDump {
-        ForceNoDump::Obsolete
-    }
-}
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum ForcePacking {
-    Zip,
-    Unzip,
-}
-
-impl Default for ForcePacking {
-    fn default() -> ForcePacking {
-        ForcePacking::Zip
+#[cfg(feature = "std")]
+impl std::str::FromStr for DataFile {
+    type Err = DatReaderError;
+    fn from_str(xml: &str) -> Result<DataFile, DatReaderError> {
+        DatReader::from_string(xml).read_all()
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct RomCenter {
-    pub plugin: String,
-    pub rom_mode: RomMode,
-    pub bios_mode: RomMode,
-    pub sample_mode: SampleMode,
-    pub lock_rom_mode: bool,
-    pub lock_bios_mode: bool,
-    pub lock_sample_mode: bool,
+#[cfg(feature = "std")]
+pub struct DatReader<B> {
+    reader: quick_xml::Reader<B>,
+    buf: Vec<u8>,
+    peeked: Option<Event<'static>>,
+    prolog_read: bool,
+    doctype: Option<String>,
+    warnings: Vec<ParseWarning>,
+    options: ReaderOptions,
+    rom_attr_handlers: HashMap<String, RomAttrHandler>,
+    rom_count: usize,
+    include_dir: Option<PathBuf>,
+    include_root: Option<PathBuf>,
+    include_stack: Vec<PathBuf>,
+    progress_callback: Option<ProgressCallback>,
+    games_read: usize,
+    data_file: Option<DataFile>,
+    open_elements: Vec<OpenElement>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum RomMode {
-    Merged,
-    Split,
-    Unmerged,
+// An element that has started but not ended yet
+#[cfg(feature = "std")]
+enum OpenElement {
+    Known {
+        tag: &'static str,
+        // Names of the children so far, for `set_reject_duplicates`
+        names: HashSet<String>,
+    },
+    Unknown(UnknownElement),
+    // Skipped along with its contents. Counts the open elements inside it.
+    Skipped(usize),
 }
 
-impl Default for RomMode {
-    fn default() -> RomMode {
-        RomMode::Split
-    }
-}
+#[cfg(feature = "std")]
+type RomAttrHandler = Box<dyn FnMut(&str, &str, usize) + Send>;
+#[cfg(feature = "std")]
+type ProgressCallback = Box<dyn FnMut(ProgressInfo) + Send>;
 
+#[cfg(feature = "std")]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum SampleMode {
-    Merged,
-    Unmerged,
-}
-
-impl Default for SampleMode {
-    fn default() -> SampleMode {
-        SampleMode::Merged
-    }
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Game {
-    pub id: String, // No-Intro extension
-    pub name: String,
-    pub description: String,
-    pub is_bios: bool,
-    pub source_file: String,
-    pub clone_of: String,
-    pub rom_of: String,
-    pub sample_of: String,
-    pub board: String,
-    pub rebuild_to: String,
-    pub comments: Vec<String>,
-    pub year: String,
-    pub manufacturer: String,
-    pub releases: Vec<Release>,
-    pub bios_sets: Vec<BiosSet>,
-    pub roms: Vec<Rom>,
-    pub disks: Vec<Disk>,
-    pub samples: Vec<Sample>,
-    pub archives: Vec<Archive>,
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Release {
-    pub name: String,
-    pub region: String,
-    pub language: String,
-    pub date: String,
-    pub default: bool,
+pub struct ProgressInfo {
+    /// Byte offset into the input after the game
+    pub position: u64,
+    /// Number of games read so far
+    pub games: usize,
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct BiosSet {
-    pub name: String,
-    pub description: String,
-    pub default: bool,
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RecoveryMode {
+    #[default]
+    Abort,
+    /// Drops a game whose contents fail to parse, records a `ParseWarning::SkippedGame`, and
+    /// continues with the next game. XML syntax errors still abort.
+    SkipBadGames,
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Rom {
-    pub name: String,
-    pub size: String,
-    pub crc: String,
-    pub sha1: String,
-    pub sha256: String, // No-Intro extension
-    pub md5: String,
-    pub merge: String,
-    pub status: Status,
-    pub date: String,
-    pub serial: String, // No-Intro extension
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ReaderOptions {
+    strict: bool,
+    strict_toplevel: bool,
+    preserve_unknown: bool,
+    reject_duplicates: bool,
+    require_rom_fields: bool,
+    require_logiqx_doctype: bool,
+    lenient_bools: bool,
+    recovery_mode: RecoveryMode,
+    preserve_comments: bool,
+    preserve_processing_instructions: bool,
+    reject_duplicate_attrs: bool,
+    max_depth: usize,
+    max_value_len: usize,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
-pub enum Status {
-    BadDump,
-    NoDump,
-    Good,
-    Verified,
-}
+#[cfg(feature = "std")]
+const LOGIQX_PUBLIC_ID: &str = "-//Logiqx//DTD ROM Management Datafile//EN";
 
-impl Default for Status {
-    fn default() -> Status {
-        Status::Good
+#[cfg(feature = "std")]
+impl Default for ReaderOptions {
+    fn default() -> ReaderOptions {
+        ReaderOptions {
+            strict: true,
+            strict_toplevel: true,
+            preserve_unknown: false,
+            reject_duplicates: false,
+            require_rom_fields: false,
+            require_logiqx_doctype: false,
+            lenient_bools: false,
+            recovery_mode: RecoveryMode::Abort,
+            preserve_comments: false,
+            preserve_processing_instructions: false,
+            reject_duplicate_attrs: true,
+            max_depth: 64,
+            max_value_len: usize::MAX,
+        }
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Disk {
-    pub name: String,
-    pub sha1: String,
-    pub md5: String,
-    pub merge: String,
-    pub status: Status,
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Sample {
-    pub name: String,
-}
-
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Archive {
-    pub name: String,
-}
-
-pub struct DatReader<B: BufRead> {
-    reader: quick_xml::Reader<B>,
-    buf: Vec<u8>,
-    strict: bool,
-}
-
-impl<'a> DatReader<&'a [u8]> {
+#[cfg(feature = "std")]
+impl DatReader<&[u8]> {
     pub fn from_string(xml: &str) -> DatReader<&[u8]> {
         DatReader::from_xml_reader(quick_xml::Reader::from_str(xml))
     }
+    /// Unlike `from_string`, the encoding is detected from a BOM or the XML declaration.
+    pub fn from_bytes(xml: &[u8]) -> DatReader<&[u8]> {
+        DatReader::from_xml_reader(quick_xml::Reader::from_reader(xml))
+    }
 }
 
+#[cfg(feature = "std")]
 impl<B: BufRead> DatReader<B> {
     pub fn from_reader(reader: B) -> DatReader<B> {
         DatReader::from_xml_reader(quick_xml::Reader::from_reader(reader))
     }
+    /// Uses `buf` as the internal event buffer. It is cleared before use, and can be taken back
+    /// with `read_all_keep_buf` for the next file.
+    pub fn from_reader_with_buf(reader: B, mut buf: Vec<u8>) -> DatReader<B> {
+        buf.clear();
+        DatReader {
+            buf,
+            ..DatReader::from_reader(reader)
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 impl DatReader<BufReader<File>> {
     pub fn from_file<P: AsRef<Path>>(
         path: P,
@@ -237,178 +252,1072 @@ impl DatReader<BufReader<File>> {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub enum DatReaderError {
     Xml(quick_xml::Error),
     UnexpectedAttribute(String),
     UnexpectedElement(String),
     UnexpectedEof(String),
+    DuplicateName(String),
+    MissingField(String),
+    UnexpectedDocType(String),
+    MalformedAttribute(String),
+    /// A recognized attribute whose value couldn't be parsed
+    InvalidAttributeValue {
+        element: String,
+        key: String,
+        value: String,
+    },
+    Include(String),
+    UnsupportedFormat(String),
+    MaxDepthExceeded(String),
+    ValueTooLong(String),
+    #[cfg(feature = "zip")]
+    Zip(zip::result::ZipError),
+    #[cfg(feature = "zip")]
+    ZipEntry(String),
+}
+
+#[cfg(feature = "std")]
+impl DatReaderError {
+    /// True if the input ended early, e.g. because of a truncated download
+    pub fn is_eof(&self) -> bool {
+        use quick_xml::errors::SyntaxError::*;
+        match self {
+            DatReaderError::UnexpectedEof(_) => true,
+            DatReaderError::Xml(quick_xml::Error::Syntax(err)) => matches!(
+                err,
+                UnclosedPIOrXmlDecl
+                    | UnclosedComment
+                    | UnclosedDoctype
+                    | UnclosedCData
+                    | UnclosedTag
+            ),
+            _ => false,
+        }
+    }
+    /// True if the input is well-formed XML that doesn't match the expected DAT structure
+    pub fn is_schema_error(&self) -> bool {
+        matches!(
+            self,
+            DatReaderError::UnexpectedAttribute(_)
+                | DatReaderError::UnexpectedElement(_)
+                | DatReaderError::MissingField(_)
+                | DatReaderError::InvalidAttributeValue { .. }
+        )
+    }
+    /// True if the input isn't well-formed XML
+    pub fn is_xml_error(&self) -> bool {
+        matches!(
+            self,
+            DatReaderError::Xml(_) | DatReaderError::MalformedAttribute(_)
+        )
+    }
 }
 
+#[cfg(feature = "std")]
 impl Error for DatReaderError {}
 
+#[cfg(feature = "std")]
 impl fmt::Display for DatReaderError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use crate::DatReaderError::*;
         match self {
             Xml(err) => write!(f, "{}", err),
-            UnexpectedAttribute(msg) | UnexpectedElement(msg) | UnexpectedEof(msg) => {
-                write!(f, "{}", msg)
-            }
+            UnexpectedAttribute(msg)
+            | UnexpectedElement(msg)
+            | UnexpectedEof(msg)
+            | DuplicateName(msg)
+            | MissingField(msg)
+            | UnexpectedDocType(msg)
+            | MalformedAttribute(msg)
+            | Include(msg)
+            | UnsupportedFormat(msg)
+            | MaxDepthExceeded(msg)
+            | ValueTooLong(msg) => write!(f, "{}", msg),
+            InvalidAttributeValue {
+                element,
+                key,
+                value,
+            } => write!(
+                f,
+                "Invalid value \"{}\" for attribute \"{}\" in element \"{}\"",
+                value, key, element
+            ),
+            #[cfg(feature = "zip")]
+            Zip(err) => write!(f, "{}", err),
+            #[cfg(feature = "zip")]
+            ZipEntry(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Something skipped while reading in non-strict mode. Positions are byte offsets into the input.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseWarning {
+    UnexpectedElement {
+        tag: String,
+        parent: String,
+        position: u64,
+    },
+    UnexpectedAttribute {
+        key: String,
+        value: String,
+        tag: String,
+        position: u64,
+    },
+    InvalidAttributeValue {
+        key: String,
+        value: String,
+        tag: String,
+        position: u64,
+    },
+    SkippedGame {
+        name: String,
+        error: String,
+        position: u64,
+    },
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseWarning::UnexpectedElement {
+                tag,
+                parent,
+                position,
+            } if parent.is_empty() => write!(
+                f,
+                "Skipped top-level element \"{}\" at position {}",
+                tag, position
+            ),
+            ParseWarning::UnexpectedElement {
+                tag,
+                parent,
+                position,
+            } => write!(
+                f,
+                "Skipped child element \"{}\" in element \"{}\" at position {}",
+                tag, parent, position
+            ),
+            ParseWarning::UnexpectedAttribute {
+                key,
+                value,
+                tag,
+                position,
+            } => write!(
+                f,
+                "Skipped attribute \"{}\"=\"{}\" in element \"{}\" at position {}",
+                key, value, tag, position
+            ),
+            ParseWarning::InvalidAttributeValue {
+                key,
+                value,
+                tag,
+                position,
+            } => write!(
+                f,
+                "Ignored invalid value \"{}\" for attribute \"{}\" in element \"{}\" at position {}",
+                value, key, tag, position
+            ),
+            ParseWarning::SkippedGame {
+                name,
+                error,
+                position,
+            } => write!(
+                f,
+                "Skipped game \"{}\" at position {}: {}",
+                name, position, error
+            ),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl From<quick_xml::Error> for DatReaderError {
     fn from(e: quick_xml::Error) -> DatReaderError {
         DatReaderError::Xml(e)
     }
 }
 
-impl<B: BufRead> DatReader<B> {
-    fn from_xml_reader(mut reader: quick_xml::Reader<B>) -> DatReader<B> {
+#[cfg(all(feature = "std", feature = "zip"))]
+impl From<zip::result::ZipError> for DatReaderError {
+    fn from(e: zip::result::ZipError) -> DatReaderError {
+        DatReaderError::Zip(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B> DatReader<B> {
+    pub(crate) fn from_xml_reader(mut reader: quick_xml::Reader<B>) -> DatReader<B> {
         reader.config_mut().trim_text(true);
         reader.config_mut().expand_empty_elements = true;
         DatReader {
             reader,
             buf: Vec::new(),
-            strict: true,
+            peeked: None,
+            prolog_read: false,
+            doctype: None,
+            warnings: Vec::new(),
+            rom_attr_handlers: HashMap::new(),
+            rom_count: 0,
+            include_dir: None,
+            include_root: None,
+            include_stack: Vec::new(),
+            progress_callback: None,
+            games_read: 0,
+            options: ReaderOptions::default(),
+            data_file: None,
+            open_elements: Vec::new(),
         }
     }
+    /// Also sets `set_strict_toplevel`, so call that afterwards to control it separately.
     pub fn set_strict(&mut self, strict: bool) {
-        self.strict = strict;
-    }
-    pub fn read_all(mut self) -> Result<DataFile, DatReaderError> {
-        let mut result: Option<DataFile> = None;
-        loop {
-            match self.reader.read_event_into(&mut self.buf)? {
-                Event::Start(ref e) => {
-                    let tag = self.reader.decoder().decode(e.name().into_inner())?;
-                    match tag.borrow() {
-                        "datafile" => {
-                            let mut cursor = XmlCursor {
-                                tag: "datafile",
-                                element: result.get_or_insert_with(Default::default),
-                            };
-                            cursor.apply_attrs(&self.reader, e.attributes(), self.strict)?;
-                            self.read_content(cursor)?;
-                        }
-                        _ => {
-                            if self.strict {
-                                break Err(DatReaderError::UnexpectedElement(format!(
-                                    "Unexpected top-level element \"{}\"",
-                                    tag
-                                )));
-                            } else {
-                                self.skip_content()?;
-                            }
-                        }
-                    }
+        self.options.strict = strict;
+        self.options.strict_toplevel = strict;
+    }
+    /// Controls whether unrecognized elements next to the root element are fatal. When
+    /// disabled, they are skipped with a warning even if the rest of the file is read strictly.
+    pub fn set_strict_toplevel(&mut self, strict_toplevel: bool) {
+        self.options.strict_toplevel = strict_toplevel;
+    }
+    /// Captures unrecognized attributes and elements into the `unknown` field of the nearest
+    /// parent instead of discarding them. Captured content is accepted even in strict mode.
+    pub fn set_preserve_unknown(&mut self, preserve_unknown: bool) {
+        self.options.preserve_unknown = preserve_unknown;
+    }
+    /// Fails on the second occurrence of a game name, or a rom name within a single game.
+    pub fn set_reject_duplicates(&mut self, reject_duplicates: bool) {
+        self.options.reject_duplicates = reject_duplicates;
+    }
+    /// Fails on a rom without a name, or without a size unless its status is `nodump`.
+    pub fn set_require_rom_fields(&mut self, require_rom_fields: bool) {
+        self.options.require_rom_fields = require_rom_fields;
+    }
+    /// Fails if the input has a DOCTYPE that isn't the Logiqx datafile one. A missing DOCTYPE
+    /// is accepted.
+    pub fn set_require_logiqx_doctype(&mut self, require_logiqx_doctype: bool) {
+        self.options.require_logiqx_doctype = require_logiqx_doctype;
+    }
+    /// Also accepts "", "0", "1", "false", and "true" for yes/no attributes.
+    pub fn set_lenient_bools(&mut self, lenient_bools: bool) {
+        self.options.lenient_bools = lenient_bools;
+    }
+    pub fn set_recovery_mode(&mut self, recovery_mode: RecoveryMode) {
+        self.options.recovery_mode = recovery_mode;
+    }
+    /// Captures `<!-- -->` comments inside the root element and elements that have an `unknown`
+    /// field. Comments outside the root element or inside unknown elements are still dropped.
+    pub fn set_preserve_comments(&mut self, preserve_comments: bool) {
+        self.options.preserve_comments = preserve_comments;
+    }
+    /// Like `set_preserve_comments`, but for `<?target content?>` processing instructions
+    pub fn set_preserve_processing_instructions(&mut self, preserve_processing_instructions: bool) {
+        self.options.preserve_processing_instructions = preserve_processing_instructions;
+    }
+    /// Rejects an element that repeats an attribute with `DatReaderError::MalformedAttribute`.
+    /// Enabled by default. When disabled, the last value wins.
+    pub fn set_reject_duplicate_attrs(&mut self, reject_duplicate_attrs: bool) {
+        self.options.reject_duplicate_attrs = reject_duplicate_attrs;
+    }
+    /// Rejects elements nested deeper than `max_depth` with `DatReaderError::MaxDepthExceeded`,
+    /// so hostile input can't overflow the stack. Defaults to 64.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.options.max_depth = max_depth;
+    }
+    /// Rejects attribute values and text longer than `max_value_len` bytes with
+    /// `DatReaderError::ValueTooLong`. Unlimited by default.
+    pub fn set_max_value_len(&mut self, max_value_len: usize) {
+        self.options.max_value_len = max_value_len;
+    }
+    /// Controls whether whitespace around text content is trimmed. Enabled by default.
+    pub fn set_trim_text(&mut self, trim_text: bool) {
+        self.reader.config_mut().trim_text(trim_text);
+    }
+    /// Calls `handler` with the name, value, and zero-based document position of the rom for
+    /// every occurrence of a rom attribute that `Rom` doesn't recognize itself.
+    pub fn register_rom_attr<F: FnMut(&str, &str, usize) + Send + 'static>(
+        &mut self,
+        name: &str,
+        handler: F,
+    ) {
+        self.rom_attr_handlers
+            .insert(name.to_owned(), Box::new(handler));
+    }
+    /// Calls `callback` after each game has been read, e.g. to drive a progress bar.
+    pub fn set_progress_callback<F: FnMut(ProgressInfo) + Send + 'static>(&mut self, callback: F) {
+        self.progress_callback = Some(Box::new(callback));
+    }
+    // Applies one event to the data file being built. Returns `VisitControl::Stop` once the
+    // input has ended or the visitor doesn't want more games.
+    pub(crate) fn handle_event(
+        &mut self,
+        event: Event,
+        mut visitor: Option<&mut dyn DatVisitor>,
+    ) -> Result<VisitControl, DatReaderError> {
+        let depth = self.open_elements.len();
+        let (is_start, is_empty) = match event {
+            Event::Start(_) => (true, false),
+            Event::Empty(_) => (false, true),
+            _ => (false, false),
+        };
+        let result = match event {
+            Event::Start(e) => self.start_element(&e, visitor),
+            // Elements like `<rom/>` only arrive as one event if expand_empty_elements is off
+            Event::Empty(e) => {
+                match self.start_element(&e, visitor.as_mut().map(|visitor| &mut **visitor as _)) {
+                    Ok(VisitControl::Continue) => self.end_element(visitor),
+                    result => result,
                 }
-                Event::Eof => {
-                    break result.ok_or_else(|| {
-                        DatReaderError::UnexpectedEof(
-                            "Unexpected EOF before a datafile element was seen".to_owned(),
-                        )
-                    })
+            }
+            Event::End(_) => self.end_element(visitor),
+            Event::Text(e) => self.push_text(&e.unescape()?),
+            Event::CData(e) => self.push_text(&self.reader.decoder().decode(&e)?),
+            Event::Comment(e) if self.options.preserve_comments => {
+                let comment = self.reader.decoder().decode(&e)?.into_owned();
+                if let Some(unknown) = self.unknown_content() {
+                    unknown.comments.push(comment);
                 }
-                _ => (),
+                Ok(VisitControl::Continue)
             }
-        }
+            Event::PI(e) if self.options.preserve_processing_instructions => {
+                let pi = self.reader.decoder().decode(&e)?.into_owned();
+                if let Some(unknown) = self.unknown_content() {
+                    unknown.processing_instructions.push(pi);
+                }
+                Ok(VisitControl::Continue)
+            }
+            Event::DocType(e) if self.data_file.is_none() => {
+                let doctype = self.reader.decoder().decode(&e)?.trim().to_owned();
+                if self.options.require_logiqx_doctype
+                    && !(doctype.starts_with("datafile") && doctype.contains(LOGIQX_PUBLIC_ID))
+                {
+                    return Err(DatReaderError::UnexpectedDocType(format!(
+                        "Unexpected DOCTYPE \"{}\"",
+                        doctype
+                    )));
+                }
+                self.doctype = Some(doctype);
+                Ok(VisitControl::Continue)
+            }
+            Event::Eof => return self.end_of_input(),
+            _ => Ok(VisitControl::Continue),
+        };
+        result.or_else(|err| self.skip_bad_game(err, depth, is_start, is_empty))
     }
-    fn skip_content(&mut self) -> Result<(), DatReaderError> {
-        let mut level = 1;
-        loop {
-            match self.reader.read_event_into(&mut self.buf)? {
-                Event::Start(_) => {
-                    level += 1;
+    fn start_element(
+        &mut self,
+        e: &BytesStart,
+        mut visitor: Option<&mut dyn DatVisitor>,
+    ) -> Result<VisitControl, DatReaderError> {
+        let decoder = self.reader.decoder();
+        let position = self.reader.buffer_position();
+        let tag = decoder.decode(e.name().into_inner())?;
+        let depth = self.open_elements.len() + 1;
+        match self.open_elements.last_mut() {
+            None => return self.start_root(e, &tag),
+            Some(OpenElement::Skipped(level)) => {
+                *level += 1;
+                return Ok(VisitControl::Continue);
+            }
+            Some(OpenElement::Unknown(_)) => {
+                check_depth(depth, self.options, &tag)?;
+                let element = UnknownElement {
+                    tag: tag.into_owned(),
+                    attrs: decode_attrs(decoder, e.attributes(), self.options)?,
+                    ..UnknownElement::default()
+                };
+                self.open_elements.push(OpenElement::Unknown(element));
+                return Ok(VisitControl::Continue);
+            }
+            Some(OpenElement::Known { .. }) => (),
+        }
+        check_depth(depth, self.options, &tag)?;
+        if tag == "include" && self.include_dir.is_some() && self.open_elements.len() == 1 {
+            let href = decode_attrs(decoder, e.attributes(), self.options)?
+                .into_iter()
+                .find(|(key, _)| key == "href")
+                .map(|(_, value)| value);
+            let games = self.read_include(href)?;
+            // Included games are handled just like the ones read from this input
+            for game in games {
+                let tag = match game.element_kind {
+                    GameElementKind::Game => "game",
+                    GameElementKind::Machine => "machine",
+                };
+                let data_file = self.data_file.as_mut().expect("the root element is open");
+                data_file.push_game(game);
+                let visitor = visitor.as_mut().map(|visitor| &mut **visitor as _);
+                if self.finish_child(tag, visitor)? == VisitControl::Stop {
+                    return Ok(VisitControl::Stop);
                 }
-                Event::End(_) => {
-                    level -= 1;
-                    if level == 0 {
-                        break Ok(());
+            }
+            self.open_elements.push(OpenElement::Skipped(0));
+            return Ok(VisitControl::Continue);
+        }
+        let parent =
+            innermost(&mut self.data_file, &self.open_elements).expect("the root element is open");
+        if let Some(mut child) = parent.element.child(&tag, true) {
+            let child_tag = child.tag;
+            self.open_elements.push(OpenElement::Known {
+                tag: child_tag,
+                names: HashSet::new(),
+            });
+            let handlers = &mut self.rom_attr_handlers;
+            let rom_index = self.rom_count;
+            let result = child.apply_attrs(
+                decoder,
+                position,
+                e,
+                self.options,
+                &mut self.warnings,
+                &mut |key, value| match handlers.get_mut(key) {
+                    Some(handler) if child_tag == "rom" => {
+                        handler(key, value, rom_index);
+                        true
                     }
+                    _ => false,
+                },
+            );
+            if child_tag == "rom" {
+                self.rom_count += 1;
+            }
+            result.map(|_| VisitControl::Continue)
+        } else if self.options.preserve_unknown && parent.element.unknown().is_some() {
+            let element = UnknownElement {
+                tag: tag.into_owned(),
+                attrs: decode_attrs(decoder, e.attributes(), self.options)?,
+                ..UnknownElement::default()
+            };
+            self.open_elements.push(OpenElement::Unknown(element));
+            Ok(VisitControl::Continue)
+        } else if self.options.strict {
+            Err(DatReaderError::UnexpectedElement(format!(
+                "Unexpected child element \"{}\" in element \"{}\"",
+                tag, parent.tag,
+            )))
+        } else {
+            self.warnings.push(ParseWarning::UnexpectedElement {
+                tag: tag.into_owned(),
+                parent: parent.tag.to_owned(),
+                position,
+            });
+            self.open_elements.push(OpenElement::Skipped(0));
+            Ok(VisitControl::Continue)
+        }
+    }
+    fn start_root(&mut self, e: &BytesStart, tag: &str) -> Result<VisitControl, DatReaderError> {
+        let (tag, element_kind) = match tag {
+            "datafile" => ("datafile", DataFileElementKind::DataFile),
+            "mame" => ("mame", DataFileElementKind::Mame),
+            _ if self.options.strict_toplevel => {
+                return Err(DatReaderError::UnexpectedElement(format!(
+                    "Unexpected top-level element \"{}\"",
+                    tag
+                )));
+            }
+            _ => {
+                self.warnings.push(ParseWarning::UnexpectedElement {
+                    tag: tag.to_owned(),
+                    parent: String::new(),
+                    position: self.reader.buffer_position(),
+                });
+                self.open_elements.push(OpenElement::Skipped(0));
+                return Ok(VisitControl::Continue);
+            }
+        };
+        check_depth(1, self.options, tag)?;
+        let data_file = self.data_file.get_or_insert_with(Default::default);
+        data_file.element_kind = element_kind;
+        self.open_elements.push(OpenElement::Known {
+            tag,
+            names: HashSet::new(),
+        });
+        let mut cursor = XmlCursor {
+            tag,
+            element: data_file,
+        };
+        cursor.apply_attrs(
+            self.reader.decoder(),
+            self.reader.buffer_position(),
+            e,
+            self.options,
+            &mut self.warnings,
+            &mut |_, _| false,
+        )?;
+        Ok(VisitControl::Continue)
+    }
+    fn end_element(
+        &mut self,
+        visitor: Option<&mut dyn DatVisitor>,
+    ) -> Result<VisitControl, DatReaderError> {
+        let tag = match self.open_elements.pop() {
+            Some(OpenElement::Known { tag, .. }) => tag,
+            Some(OpenElement::Unknown(element)) => {
+                if let Some(OpenElement::Unknown(parent)) = self.open_elements.last_mut() {
+                    parent.children.push(element);
+                } else if let Some(unknown) = self.unknown_content() {
+                    unknown.elements.push(element);
+                }
+                return Ok(VisitControl::Continue);
+            }
+            Some(OpenElement::Skipped(level)) if level > 0 => {
+                self.open_elements.push(OpenElement::Skipped(level - 1));
+                return Ok(VisitControl::Continue);
+            }
+            Some(OpenElement::Skipped(_)) | None => return Ok(VisitControl::Continue),
+        };
+        self.finish_child(tag, visitor)
+    }
+    // Checks the last child of the innermost open element once it's complete, and hands it to
+    // the visitor if it's a child of the root element
+    fn finish_child(
+        &mut self,
+        tag: &'static str,
+        visitor: Option<&mut dyn DatVisitor>,
+    ) -> Result<VisitControl, DatReaderError> {
+        let parent = match innermost(&mut self.data_file, &self.open_elements) {
+            Some(parent) => parent,
+            None => return Ok(VisitControl::Continue),
+        };
+        if self.options.require_rom_fields {
+            if let Some(field) = parent
+                .element
+                .child(tag, false)
+                .and_then(|child| child.element.missing_field())
+            {
+                return Err(DatReaderError::MissingField(format!(
+                    "Missing required attribute \"{}\" in element \"{}\"",
+                    field, tag
+                )));
+            }
+        }
+        if matches!(tag, "game" | "machine") {
+            self.games_read += 1;
+            if let Some(callback) = &mut self.progress_callback {
+                callback(ProgressInfo {
+                    position: self.reader.buffer_position(),
+                    games: self.games_read,
+                });
+            }
+        }
+        if self.options.reject_duplicates {
+            if let (Some(name), Some(OpenElement::Known { names, .. })) = (
+                parent.element.child_name(tag),
+                self.open_elements.last_mut(),
+            ) {
+                if !names.insert(name.to_owned()) {
+                    return Err(DatReaderError::DuplicateName(format!(
+                        "Duplicate {} name \"{}\" in element \"{}\"",
+                        tag, name, parent.tag
+                    )));
                 }
-                Event::Eof => break Ok(()),
-                _ => (),
             }
         }
+        match visitor {
+            Some(visitor) if self.open_elements.len() == 1 => {
+                Ok(parent.element.visit(tag, visitor))
+            }
+            _ => Ok(VisitControl::Continue),
+        }
     }
-    fn read_content(&mut self, cursor: XmlCursor) -> Result<(), DatReaderError> {
-        loop {
-            match self.reader.read_event_into(&mut self.buf)? {
-                Event::Start(e) => {
-                    let tag = self.reader.decoder().decode(e.name().into_inner())?;
-                    if let Some(mut child) = cursor.element.child(&tag) {
-                        child.apply_attrs(&self.reader, e.attributes(), self.strict)?;
-                        self.read_content(child)?;
-                    } else if self.strict {
-                        break Err(DatReaderError::UnexpectedElement(format!(
-                            "Unexpected child element \"{}\" in element \"{}\"",
-                            tag, cursor.tag,
-                        )));
-                    } else {
-                        self.skip_content()?;
-                    }
+    fn push_text(&mut self, text: &str) -> Result<VisitControl, DatReaderError> {
+        match self.open_elements.last_mut() {
+            Some(OpenElement::Unknown(element)) => {
+                element.text.push_str(text);
+                check_text_len(&element.text, self.options, &element.tag)?;
+            }
+            Some(OpenElement::Known { .. }) => {
+                let cursor = innermost(&mut self.data_file, &self.open_elements)
+                    .expect("the root element is open");
+                if let Some(content) = cursor.element.content() {
+                    content.push_str(text);
+                    check_text_len(content, self.options, cursor.tag)?;
                 }
-                Event::Text(e) => {
-                    if let Some(content) = cursor.element.content() {
-                        content.push_str(&e.unescape()?);
-                    }
+            }
+            Some(OpenElement::Skipped(_)) | None => (),
+        }
+        Ok(VisitControl::Continue)
+    }
+    // Captured comments and unknown elements go to the innermost open element of the data model
+    fn unknown_content(&mut self) -> Option<&mut UnknownContent> {
+        if !matches!(self.open_elements.last(), Some(OpenElement::Known { .. })) {
+            return None;
+        }
+        let element = innermost(&mut self.data_file, &self.open_elements)?.element;
+        element.unknown()
+    }
+    fn end_of_input(&self) -> Result<VisitControl, DatReaderError> {
+        let open = self
+            .open_elements
+            .iter()
+            .rev()
+            .find_map(|element| match element {
+                OpenElement::Known { tag, .. } => Some(*tag),
+                OpenElement::Unknown(element) => Some(element.tag.as_str()),
+                OpenElement::Skipped(_) => None,
+            });
+        match open {
+            Some(tag) => Err(DatReaderError::UnexpectedEof(format!(
+                "Unexpected EOF while reading element \"{}\"",
+                tag
+            ))),
+            None if self.data_file.is_none() => Err(DatReaderError::UnexpectedEof(
+                "Unexpected EOF before a datafile element was seen".to_owned(),
+            )),
+            None => Ok(VisitControl::Stop),
+        }
+    }
+    // With `RecoveryMode::SkipBadGames`, drops the game that `err` happened in and skips the
+    // rest of it. `depth` is the number of open elements before the failed event.
+    fn skip_bad_game(
+        &mut self,
+        err: DatReaderError,
+        depth: usize,
+        is_start: bool,
+        is_empty: bool,
+    ) -> Result<VisitControl, DatReaderError> {
+        let tag = match self.open_elements.get(1) {
+            Some(OpenElement::Known { tag, .. }) if matches!(*tag, "game" | "machine") => *tag,
+            _ => return Err(err),
+        };
+        if self.options.recovery_mode != RecoveryMode::SkipBadGames || !is_recoverable(&err) {
+            return Err(err);
+        }
+        // Elements of the game that the input hasn't closed yet
+        let mut open = self.open_elements.len() - 1;
+        if is_start && self.open_elements.len() == depth {
+            // Failed before it was tracked
+            open += 1;
+        }
+        if is_empty && self.open_elements.len() == depth + 1 {
+            // Failed after it was tracked, but it has no end event
+            open -= 1;
+        }
+        let data_file = self.data_file.as_mut().expect("the root element is open");
+        let name = data_file.child_name(tag).unwrap_or_default().to_owned();
+        data_file.discard_child(tag);
+        self.open_elements.truncate(1);
+        if open > 0 {
+            self.open_elements.push(OpenElement::Skipped(open - 1));
+        }
+        self.warnings.push(ParseWarning::SkippedGame {
+            name,
+            error: err.to_string(),
+            position: self.reader.buffer_position(),
+        });
+        Ok(VisitControl::Continue)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: BufRead> DatReader<B> {
+    /// Returns the name of the encoding used to decode the input, e.g. "UTF-8" or
+    /// "windows-1252". The XML declaration is read on the first call if needed.
+    pub fn encoding(&mut self) -> Result<&'static str, DatReaderError> {
+        self.read_prolog()?;
+        Ok(self.reader.decoder().encoding().name())
+    }
+    /// Returns the DOCTYPE declaration, e.g. `datafile PUBLIC "..." "..."`, if the input has one.
+    pub fn doctype(&mut self) -> Result<Option<&str>, DatReaderError> {
+        self.read_prolog()?;
+        Ok(self.doctype.as_deref())
+    }
+    // Reads everything before the first element
+    fn read_prolog(&mut self) -> Result<(), DatReaderError> {
+        if self.prolog_read {
+            return Ok(());
+        }
+        self.prolog_read = true;
+        let mut buf = mem::take(&mut self.buf);
+        let result = loop {
+            buf.clear();
+            match self.reader.read_event_into(&mut buf) {
+                Ok(event @ (Event::Start(_) | Event::Empty(_))) => {
+                    self.peeked = Some(event.into_owned());
+                    break Ok(());
                 }
-                Event::CData(e) => {
-                    if let Some(content) = cursor.element.content() {
-                        content.push_str(&self.reader.decoder().decode(&e)?);
+                Ok(Event::Eof) => break Ok(()),
+                Ok(event) => {
+                    if let Err(err) = self.handle_event(event, None) {
+                        break Err(err);
                     }
                 }
-                Event::End(_) => break Ok(()),
-                Event::Eof => {
-                    break Err(DatReaderError::UnexpectedEof(format!(
-                        "Unexpected EOF while reading element \"{}\"",
-                        cursor.tag
-                    )));
-                }
-                _ => (),
+                Err(err) => break Err(err.into()),
+            }
+        };
+        self.buf = buf;
+        result
+    }
+    /// Like `read_all`, but also hands back the internal buffer for reuse.
+    pub fn read_all_keep_buf(mut self) -> (Result<DataFile, DatReaderError>, Vec<u8>) {
+        let result = self.read_data_file(None);
+        (result, self.buf)
+    }
+    pub fn read_all(self) -> Result<DataFile, DatReaderError> {
+        self.read_all_with_warnings()
+            .map(|(data_file, _)| data_file)
+    }
+    /// Like `read_all`, but also returns everything that was skipped in non-strict mode.
+    pub fn read_all_with_warnings(
+        mut self,
+    ) -> Result<(DataFile, Vec<ParseWarning>), DatReaderError> {
+        let data_file = self.read_data_file(None)?;
+        Ok((data_file, self.warnings))
+    }
+    /// Appends the games to `target`. The header is only taken if `target` doesn't have one
+    /// yet, and the root element's attributes are ignored, so `target` keeps its own.
+    pub fn read_into(self, target: &mut DataFile) -> Result<(), DatReaderError> {
+        let data_file = self.read_all()?;
+        if target.header.is_none() {
+            target.header = data_file.header;
+        }
+        target.games.extend(data_file.games);
+        Ok(())
+    }
+    /// Reports the header and each game to the visitor as soon as it has been read. Games are
+    /// not kept in memory.
+    pub fn read_with_visitor<V: DatVisitor>(
+        mut self,
+        visitor: &mut V,
+    ) -> Result<(), DatReaderError> {
+        self.read_data_file(Some(visitor)).map(|_| ())
+    }
+    fn read_data_file(
+        &mut self,
+        mut visitor: Option<&mut dyn DatVisitor>,
+    ) -> Result<DataFile, DatReaderError> {
+        self.read_prolog()?;
+        let mut buf = mem::take(&mut self.buf);
+        let result = loop {
+            buf.clear();
+            let event = match self.peeked.take() {
+                Some(event) => event,
+                None => match self.reader.read_event_into(&mut buf) {
+                    Ok(event) => event,
+                    Err(err) => break Err(err.into()),
+                },
             };
+            match self.handle_event(event, visitor.as_mut().map(|visitor| &mut **visitor as _)) {
+                Ok(VisitControl::Continue) => (),
+                Ok(VisitControl::Stop) => break Ok(self.data_file.take().unwrap_or_default()),
+                Err(err) => break Err(err),
+            }
+        };
+        self.buf = buf;
+        result
+    }
+}
+
+// Finds the innermost open element of the data model
+#[cfg(feature = "std")]
+fn innermost<'a>(
+    data_file: &'a mut Option<DataFile>,
+    open_elements: &[OpenElement],
+) -> Option<XmlCursor<'a>> {
+    let mut tags = open_elements.iter().map_while(|element| match element {
+        OpenElement::Known { tag, .. } => Some(*tag),
+        _ => None,
+    });
+    let mut cursor = XmlCursor {
+        tag: tags.next()?,
+        element: data_file.as_mut()?,
+    };
+    for tag in tags {
+        let element = cursor.element;
+        cursor = element.child(tag, false)?;
+    }
+    Some(cursor)
+}
+
+#[cfg(feature = "std")]
+fn check_depth(depth: usize, options: ReaderOptions, tag: &str) -> Result<(), DatReaderError> {
+    if depth > options.max_depth {
+        return Err(DatReaderError::MaxDepthExceeded(format!(
+            "Element \"{}\" is nested deeper than the limit of {}",
+            tag, options.max_depth
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn check_text_len(text: &str, options: ReaderOptions, tag: &str) -> Result<(), DatReaderError> {
+    if text.len() > options.max_value_len {
+        return Err(DatReaderError::ValueTooLong(format!(
+            "Text in element \"{}\" is longer than the limit of {} bytes",
+            tag, options.max_value_len
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn check_attr_len(key: &str, value: &[u8], options: ReaderOptions) -> Result<(), DatReaderError> {
+    if value.len() > options.max_value_len {
+        return Err(DatReaderError::ValueTooLong(format!(
+            "Value of attribute \"{}\" is longer than the limit of {} bytes",
+            key, options.max_value_len
+        )));
+    }
+    Ok(())
+}
+
+// Errors detected by this crate leave the XML reader in a consistent state, unlike syntax errors
+#[cfg(feature = "std")]
+fn is_recoverable(err: &DatReaderError) -> bool {
+    matches!(
+        err,
+        DatReaderError::UnexpectedAttribute(_)
+            | DatReaderError::UnexpectedElement(_)
+            | DatReaderError::DuplicateName(_)
+            | DatReaderError::MissingField(_)
+            | DatReaderError::MalformedAttribute(_)
+            | DatReaderError::InvalidAttributeValue { .. }
+    )
+}
+
+#[cfg(feature = "std")]
+fn decode_attrs(
+    decoder: Decoder,
+    attrs: Attributes,
+    options: ReaderOptions,
+) -> Result<Vec<(String, String)>, DatReaderError> {
+    let mut result = Vec::new();
+    for attr in attrs {
+        let attr = attr.map_err(quick_xml::Error::InvalidAttr)?;
+        let key = decoder.decode(attr.key.into_inner())?;
+        check_attr_len(&key, &attr.value, options)?;
+        let value = attr.decode_and_unescape_value(decoder)?;
+        result.push((key.into_owned(), value.into_owned()));
+    }
+    Ok(result)
+}
+
+/// How `DatWriter` writes yes/no attributes
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum BoolStyle {
+    /// Writes both "yes" and "no"
+    YesNo,
+    /// Writes "yes" and leaves out "no", like Logiqx DATs usually do
+    #[default]
+    OmitFalse,
+}
+
+#[cfg(feature = "std")]
+pub struct DatWriter<W: Write> {
+    writer: XmlWriter<W>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DatWriter<W> {
+    pub fn from_writer(writer: W) -> DatWriter<W> {
+        DatWriter {
+            writer: XmlWriter::new(writer),
+        }
+    }
+    pub fn set_bool_style(&mut self, bool_style: BoolStyle) {
+        self.writer.bool_style = bool_style;
+    }
+    /// Controls whether empty text elements and attributes are left out. Enabled by default.
+    /// When disabled, they are written as e.g. `<year/>` and `cloneof=""`.
+    pub fn set_omit_empty(&mut self, omit_empty: bool) {
+        self.writer.omit_empty = omit_empty;
+    }
+    pub fn write_all(mut self, data_file: &DataFile) -> Result<W, DatWriterError> {
+        let tag = match data_file.element_kind {
+            DataFileElementKind::DataFile => "datafile",
+            DataFileElementKind::Mame => "mame",
+        };
+        self.writer.prolog(tag)?;
+        self.writer.element(tag, data_file)?;
+        self.writer.finish()
+    }
+    /// Starts writing a `<datafile>` with the given header, to be followed by games one at a
+    /// time. The options set on this writer apply to the streamed output too.
+    pub fn start(mut self, header: &Header) -> Result<StreamingDatWriter<W>, DatWriterError> {
+        self.writer.prolog("datafile")?;
+        self.writer.start("datafile")?;
+        self.writer.element("header", header)?;
+        Ok(StreamingDatWriter {
+            writer: self.writer,
+        })
+    }
+}
+
+/// Writes a `<datafile>` one game at a time, so the games never need to be in memory at once
+#[cfg(feature = "std")]
+pub struct StreamingDatWriter<W: Write> {
+    writer: XmlWriter<W>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> StreamingDatWriter<W> {
+    pub fn write_game(&mut self, game: &Game) -> Result<(), DatWriterError> {
+        self.writer.game(game)
+    }
+    pub fn finish(mut self) -> Result<W, DatWriterError> {
+        self.writer.end("datafile")?;
+        self.writer.finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl DatWriter<Vec<u8>> {
+    pub fn to_string(data_file: &DataFile) -> Result<String, DatWriterError> {
+        let buf = DatWriter::from_writer(Vec::new()).write_all(data_file)?;
+        Ok(String::from_utf8(buf).expect("DatWriter produced invalid UTF-8"))
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum DatWriterError {
+    Xml(quick_xml::Error),
+    InvalidComment(String),
+    InvalidName(String),
+    InvalidProcessingInstruction(String),
+}
+
+#[cfg(feature = "std")]
+impl Error for DatWriterError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for DatWriterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::DatWriterError::*;
+        match self {
+            Xml(err) => write!(f, "{}", err),
+            InvalidComment(msg) | InvalidName(msg) | InvalidProcessingInstruction(msg) => {
+                write!(f, "{}", msg)
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
+impl From<quick_xml::Error> for DatWriterError {
+    fn from(e: quick_xml::Error) -> DatWriterError {
+        DatWriterError::Xml(e)
+    }
+}
+
+// AttrError only carries a position, so recover the offending key from the raw tag
+#[cfg(feature = "std")]
+fn malformed_attr_key(start: &BytesStart, err: &AttrError) -> String {
+    let is_key_byte = |b: &u8| !b.is_ascii_whitespace() && *b != b'=';
+    let raw: &[u8] = start;
+    let key = match *err {
+        AttrError::Duplicated(pos, _) => {
+            let rest = &raw[pos.min(raw.len())..];
+            let end = rest
+                .iter()
+                .position(|b| !is_key_byte(b))
+                .unwrap_or(rest.len());
+            &rest[..end]
+        }
+        AttrError::ExpectedEq(pos)
+        | AttrError::ExpectedValue(pos)
+        | AttrError::UnquotedValue(pos)
+        | AttrError::ExpectedQuote(pos, _) => {
+            let head = &raw[..pos.min(raw.len())];
+            let end = head.iter().rposition(is_key_byte).map_or(0, |end| end + 1);
+            let head = &head[..end];
+            let start = head
+                .iter()
+                .rposition(|b| !is_key_byte(b))
+                .map_or(0, |start| start + 1);
+            &head[start..]
+        }
+    };
+    String::from_utf8_lossy(key).into_owned()
+}
+
+#[cfg(feature = "std")]
 pub(crate) struct XmlCursor<'a> {
     tag: &'static str,
     element: &'a mut dyn XmlElement,
 }
 
+#[cfg(feature = "std")]
 impl<'a> XmlCursor<'a> {
-    fn apply_attrs<B: BufRead>(
+    fn apply_attrs(
         &mut self,
-        reader: &quick_xml::Reader<B>,
-        attrs: Attributes,
-        strict: bool,
+        decoder: Decoder,
+        position: u64,
+        start: &BytesStart,
+        options: ReaderOptions,
+        warnings: &mut Vec<ParseWarning>,
+        extra: &mut dyn FnMut(&str, &str) -> bool,
     ) -> Result<(), DatReaderError> {
+        let mut attrs = start.attributes();
+        attrs.with_checks(options.reject_duplicate_attrs);
         for attr in attrs {
-            let attr = attr.map_err(quick_xml::Error::InvalidAttr)?;
-            let key = reader.decoder().decode(attr.key.into_inner())?;
-            let value = attr.decode_and_unescape_value(reader.decoder())?;
+            let attr = attr.map_err(|err| {
+                DatReaderError::MalformedAttribute(format!(
+                    "Malformed attribute \"{}\" in element \"{}\": {}",
+                    malformed_attr_key(start, &err),
+                    self.tag,
+                    err
+                ))
+            })?;
+            let key = decoder.decode(attr.key.into_inner())?;
+            check_attr_len(&key, &attr.value, options)?;
+            let value = attr.decode_and_unescape_value(decoder)?;
             if let Some(target) = self.element.attr(&key) {
-                if target.set_from_str(&value) {
+                let valid = if options.lenient_bools {
+                    target.set_from_str_lenient(&value)
+                } else {
+                    target.set_from_str(&value)
+                };
+                if valid {
                     continue;
                 }
+                if options.strict {
+                    return Err(DatReaderError::InvalidAttributeValue {
+                        element: self.tag.to_owned(),
+                        key: key.into_owned(),
+                        value: value.into_owned(),
+                    });
+                }
+                warnings.push(ParseWarning::InvalidAttributeValue {
+                    key: key.into_owned(),
+                    value: value.into_owned(),
+                    tag: self.tag.to_owned(),
+                    position,
+                });
+                continue;
+            } else if extra(&key, &value) {
+                continue;
+            } else if let Some(unknown) =
+                self.element.unknown().filter(|_| options.preserve_unknown)
+            {
+                unknown.attrs.push((key.into_owned(), value.into_owned()));
+                continue;
             }
-            if strict {
+            if options.strict {
                 return Err(DatReaderError::UnexpectedAttribute(format!(
                     "Unexpected attribute \"{}\"=\"{}\" in element \"{}\"",
                     key, value, self.tag
                 )));
             }
+            warnings.push(ParseWarning::UnexpectedAttribute {
+                key: key.into_owned(),
+                value: value.into_owned(),
+                tag: self.tag.to_owned(),
+                position,
+            });
         }
         Ok(())
     }
 }
 
-#[test]
-fn test_full_parse() {
-    let input = r#"
+#[cfg(all(test, feature = "std"))]
+const FULL_PARSE_INPUT: &str = r#"
 <?xml version="1.0"?>
 <!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
 <datafile build="Build" debug="yes">
@@ -432,14 +1341,14 @@ fn test_full_parse() {
         <description>Description</description>
         <year>Year</year>
         <manufacturer>Manufacturer</manufacturer>
-        <release name="Name1" region="Region1" language="Language1" date="Date1" default="yes" />
-        <release name="Name2" region="Region2" language="Language2" date="Date2" default="no" />
+        <release name="Name1" region="Region1" language="Language1" date="19960227" default="yes" />
+        <release name="Name2" region="Region2" language="Language2" date="1996-09-30" default="no" />
         <biosset name="Name1" description="Description1" default="yes" />
         <biosset name="Name2" description="Description2" default="yes" />
-        <rom name="Name1" size="Size1" crc="Crc1" sha1="Sha1" sha256="Sha256" md5="Md1" merge="Merge1" status="baddump" date="Date1" serial="Serial1" />
-        <rom name="Name2" size="Size2" crc="Crc2" sha1="Sha2" sha256="Sha256" md5="Md2" merge="Merge2" status="verified" date="Date2" serial="Serial2" />
-        <disk name="Name1" sha1="Sha1" md5="Md1" merge="Merge1" status="baddump" />
-        <disk name="Name2" sha1="Sha2" md5="Md2" merge="Merge2" status="verified" />
+        <rom name="Name1" size="1024" crc="0123abcd" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709" sha256="e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855" md5="d41d8cd98f00b204e9800998ecf8427e" merge="Merge1" status="baddump" date="20220101" serial="Serial1" />
+        <rom name="Name2" size="0x800" crc="DEADBEEF" sha1="A94A8FE5CCB19BA61C4C0873D391E987982FBBD3" sha256="9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08" md5="098F6BCD4621D373CADE4E832627B4F6" merge="Merge2" status="verified" date="2022-01-02" serial="Serial2" />
+        <disk name="Name1" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709" md5="d41d8cd98f00b204e9800998ecf8427e" merge="Merge1" status="baddump" />
+        <disk name="Name2" sha1="A94A8FE5CCB19BA61C4C0873D391E987982FBBD3" md5="098F6BCD4621D373CADE4E832627B4F6" merge="Merge2" status="verified" />
         <sample name="Name1" />
         <sample name="Name2" />
         <archive name="Name1" />
@@ -449,14 +1358,21 @@ fn test_full_parse() {
         <description>Description2</description>
     </game>
 </datafile>"#;
-    let reader = DatReader::from_string(&input);
+
+#[cfg(feature = "std")]
+#[test]
+fn test_full_parse() {
+    let reader = DatReader::from_string(FULL_PARSE_INPUT);
     let data_file = reader.read_all().unwrap();
     assert_eq!(
         data_file,
         DataFile {
+            element_kind: DataFileElementKind::DataFile,
+            id: "".to_owned(),
             build: "Build".to_owned(),
             debug: true,
             header: Some(Header {
+                id: "".to_owned(),
                 name: "Name".to_owned(),
                 description: "Description".to_owned(),
                 category: "Category".to_owned(),
@@ -467,11 +1383,17 @@ fn test_full_parse() {
                 homepage: "Homepage".to_owned(),
                 url: "Url".to_owned(),
                 comment: "Comment".to_owned(),
+                subset: "".to_owned(),
+                retool: "".to_owned(),
                 clr_mame_pro: Some(ClrMamePro {
                     header: "Header".to_owned(),
                     force_merging: ForceMerging::Full,
                     force_no_dump: ForceNoDump::Ignore,
                     force_packing: ForcePacking::Unzip,
+                    version: String::new(),
+                    date: String::new(),
+                    comment: String::new(),
+                    unknown: UnknownContent::default(),
                 }),
                 rom_center: Some(RomCenter {
                     plugin: "Plugin".to_owned(),
@@ -481,16 +1403,20 @@ fn test_full_parse() {
                     lock_rom_mode: true,
                     lock_bios_mode: true,
                     lock_sample_mode: true,
-                })
+                    unknown: UnknownContent::default(),
+                }),
+                unknown: UnknownContent::default(),
             }),
             games: vec![
                 Game {
+                    element_kind: GameElementKind::Game,
                     id: "".to_owned(),
                     name: "Name".to_owned(),
                     description: "Description".to_owned(),
                     source_file: "Sourcefile".to_owned(),
                     is_bios: true,
                     clone_of: "Cloneof".to_owned(),
+                    clone_of_id: "".to_owned(),
                     rom_of: "Romof".to_owned(),
                     sample_of: "Sampleof".to_owned(),
                     board: "Board".to_owned(),
@@ -503,15 +1429,17 @@ fn test_full_parse() {
                             name: "Name1".to_owned(),
                             region: "Region1".to_owned(),
                             language: "Language1".to_owned(),
-                            date: "Date1".to_owned(),
+                            date: DatDate::new("19960227"),
                             default: true,
+                            unknown: UnknownContent::default(),
                         },
                         Release {
                             name: "Name2".to_owned(),
                             region: "Region2".to_owned(),
                             language: "Language2".to_owned(),
-                            date: "Date2".to_owned(),
+                            date: DatDate::new("1996-09-30"),
                             default: false,
+                            unknown: UnknownContent::default(),
                         }
                     ],
                     bios_sets: vec![
@@ -519,79 +1447,112 @@ fn test_full_parse() {
                             name: "Name1".to_owned(),
                             description: "Description1".to_owned(),
                             default: true,
+                            unknown: UnknownContent::default(),
                         },
                         BiosSet {
                             name: "Name2".to_owned(),
                             description: "Description2".to_owned(),
                             default: true,
+                            unknown: UnknownContent::default(),
                         }
                     ],
                     roms: vec![
                         Rom {
                             name: "Name1".to_owned(),
-                            size: "Size1".to_owned(),
-                            crc: "Crc1".to_owned(),
-                            sha1: "Sha1".to_owned(),
-                            sha256: "Sha256".to_owned(),
-                            md5: "Md1".to_owned(),
+                            size: Some(1024),
+                            crc: Some(Crc32(0x0123abcd)),
+                            sha1: Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+                            sha256: Sha256::parse(
+                                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                            ),
+                            md5: Md5::parse("d41d8cd98f00b204e9800998ecf8427e"),
                             merge: "Merge1".to_owned(),
                             status: Status::BadDump,
-                            date: "Date1".to_owned(),
-                            serial: "Serial1".to_owned()
+                            date: DatDate::new("20220101"),
+                            serial: "Serial1".to_owned(),
+                            region: "".to_owned(),
+                            flags: "".to_owned(),
+                            mia: false,
+                            bios: "".to_owned(),
+                            header_ref: "".to_owned(),
+                            track: None,
+                            number: None,
+                            unknown: UnknownContent::default(),
                         },
                         Rom {
                             name: "Name2".to_owned(),
-                            size: "Size2".to_owned(),
-                            crc: "Crc2".to_owned(),
-                            sha1: "Sha2".to_owned(),
-                            sha256: "Sha256".to_owned(),
-                            md5: "Md2".to_owned(),
+                            size: Some(2048),
+                            crc: Some(Crc32(0xdeadbeef)),
+                            sha1: Sha1::parse("a94a8fe5ccb19ba61c4c0873d391e987982fbbd3"),
+                            sha256: Sha256::parse(
+                                "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+                            ),
+                            md5: Md5::parse("098f6bcd4621d373cade4e832627b4f6"),
                             merge: "Merge2".to_owned(),
                             status: Status::Verified,
-                            date: "Date2".to_owned(),
-                            serial: "Serial2".to_owned()
+                            date: DatDate::new("2022-01-02"),
+                            serial: "Serial2".to_owned(),
+                            region: "".to_owned(),
+                            flags: "".to_owned(),
+                            mia: false,
+                            bios: "".to_owned(),
+                            header_ref: "".to_owned(),
+                            track: None,
+                            number: None,
+                            unknown: UnknownContent::default(),
                         }
                     ],
                     disks: vec![
                         Disk {
                             name: "Name1".to_owned(),
-                            sha1: "Sha1".to_owned(),
-                            md5: "Md1".to_owned(),
+                            sha1: Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+                            md5: Md5::parse("d41d8cd98f00b204e9800998ecf8427e"),
                             merge: "Merge1".to_owned(),
                             status: Status::BadDump,
+                            unknown: UnknownContent::default(),
                         },
                         Disk {
                             name: "Name2".to_owned(),
-                            sha1: "Sha2".to_owned(),
-                            md5: "Md2".to_owned(),
+                            sha1: Sha1::parse("a94a8fe5ccb19ba61c4c0873d391e987982fbbd3"),
+                            md5: Md5::parse("098f6bcd4621d373cade4e832627b4f6"),
                             merge: "Merge2".to_owned(),
                             status: Status::Verified,
+                            unknown: UnknownContent::default(),
                         },
                     ],
                     samples: vec![
                         Sample {
                             name: "Name1".to_owned(),
+                            unknown: UnknownContent::default(),
                         },
                         Sample {
                             name: "Name2".to_owned(),
+                            unknown: UnknownContent::default(),
                         }
                     ],
                     archives: vec![
                         Archive {
                             name: "Name1".to_owned(),
+                            ..Default::default()
                         },
                         Archive {
                             name: "Name2".to_owned(),
+                            ..Default::default()
                         }
                     ],
+                    device_refs: vec![],
+                    driver: None,
+                    unknown: UnknownContent::default(),
                 },
                 Game {
+                    element_kind: GameElementKind::Game,
                     id: "".to_owned(),
                     name: "Name2".to_owned(),
                     description: "Description2".to_owned(),
                     source_file: "".to_owned(),
                     is_bios: false,
                     clone_of: "".to_owned(),
+                    clone_of_id: "".to_owned(),
                     rom_of: "".to_owned(),
                     sample_of: "".to_owned(),
                     board: "".to_owned(),
@@ -605,8 +1566,1592 @@ fn test_full_parse() {
                     disks: vec![],
                     samples: vec![],
                     archives: vec![],
+                    device_refs: vec![],
+                    driver: None,
+                    unknown: UnknownContent::default(),
                 }
             ],
+            unknown: UnknownContent::default(),
         }
     );
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_round_trip() {
+    let data_file = DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap();
+    let output = DatWriter::to_string(&data_file).unwrap();
+    let reparsed = DatReader::from_string(&output).read_all().unwrap();
+    assert_eq!(data_file, reparsed);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_omits_empty() {
+    let data_file = DataFile {
+        games: vec![Game {
+            name: "Name".to_owned(),
+            description: "Description".to_owned(),
+            roms: vec![Rom {
+                name: "Name".to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert_eq!(
+        output,
+        r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile>
+    <game name="Name">
+        <description>Description</description>
+        <rom name="Name" status="good"/>
+    </game>
+</datafile>
+"#
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_hash_lengths() {
+    let read = |attrs: &str| {
+        let input = format!(
+            r#"<datafile><game name="Name"><rom name="Name" size="1" {}/></game></datafile>"#,
+            attrs
+        );
+        DatReader::from_string(&input)
+            .read_all()
+            .map(|data_file| data_file.games[0].roms[0].clone())
+    };
+    for attrs in [
+        r#"crc="0123456""#,
+        r#"crc="012345678""#,
+        r#"md5="d41d8cd98f00b204e9800998ecf8427""#,
+        r#"sha1="da39a3ee5e6b4b0d3255bfef95601890afd8070""#,
+        r#"sha256="e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85""#,
+    ] {
+        assert!(
+            matches!(
+                read(attrs),
+                Err(DatReaderError::InvalidAttributeValue { .. })
+            ),
+            "{}",
+            attrs
+        );
+    }
+    let rom = read(r#"crc="" md5="" sha1="" sha256="""#).unwrap();
+    assert_eq!(
+        (rom.crc, rom.md5, rom.sha1, rom.sha256),
+        (None, None, None, None)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_case() {
+    let input = |crc: &str, sha1: &str| {
+        format!(
+            r#"<datafile><game name="Name"><rom name="Name" size="1" crc="{}" sha1="{}"/></game></datafile>"#,
+            crc, sha1
+        )
+    };
+    let upper = DatReader::from_string(&input(
+        "DEADBEEF",
+        "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709",
+    ))
+    .read_all()
+    .unwrap();
+    let lower = DatReader::from_string(&input(
+        "deadbeef",
+        "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+    ))
+    .read_all()
+    .unwrap();
+    assert_eq!(upper, lower);
+    let output = DatWriter::to_string(&upper).unwrap();
+    assert!(output.contains(r#"crc="deadbeef" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709""#));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_archive_attrs() {
+    let input = r#"
+<datafile>
+    <game name="Game">
+        <archive name="Game (USA)" number="0042" clone="P" region="USA" languages="En,Fr" version="Rev 1" devstatus="released"/>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let archive = &data_file.games[0].archives[0];
+    assert_eq!(archive.name, "Game (USA)");
+    assert_eq!(archive.number, "0042");
+    assert_eq!(archive.clone, "P");
+    assert_eq!(archive.region, "USA");
+    assert_eq!(archive.languages, "En,Fr");
+    assert_eq!(archive.version, "Rev 1");
+    assert_eq!(archive.dev_status, "released");
+    assert_eq!(
+        DatReader::from_string(&DatWriter::to_string(&data_file).unwrap())
+            .read_all()
+            .unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_size() {
+    let parse = |size: &str, strict: bool| {
+        let input = format!(
+            r#"<datafile><game name="Name"><rom name="Name" size="{}"/></game></datafile>"#,
+            size
+        );
+        let mut reader = DatReader::from_string(&input);
+        reader.set_strict(strict);
+        reader
+            .read_all()
+            .map(|data_file| data_file.games[0].roms[0].size)
+    };
+    assert_eq!(parse("1048576", true).unwrap(), Some(1048576));
+    assert_eq!(parse("0x100000", true).unwrap(), Some(1048576));
+    assert!(matches!(
+        parse("garbage", true),
+        Err(DatReaderError::InvalidAttributeValue { .. })
+    ));
+    for size in ["+1024", "0x+400", "-1", "0x", ""] {
+        assert!(matches!(
+            parse(size, true),
+            Err(DatReaderError::InvalidAttributeValue { .. })
+        ));
+    }
+    assert_eq!(parse("garbage", false).unwrap(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_crc() {
+    let parse = |crc: &str, strict: bool| {
+        let input = format!(
+            r#"<datafile><game name="Name"><rom name="Name" crc="{}"/></game></datafile>"#,
+            crc
+        );
+        let mut reader = DatReader::from_string(&input);
+        reader.set_strict(strict);
+        reader
+            .read_all()
+            .map(|data_file| data_file.games[0].roms[0].crc)
+    };
+    assert_eq!(parse("DEADBEEF", true).unwrap(), Some(Crc32(0xdeadbeef)));
+    assert_eq!(parse("0xdeadbeef", true).unwrap(), Some(Crc32(0xdeadbeef)));
+    match parse("beef", true) {
+        Err(DatReaderError::InvalidAttributeValue {
+            element,
+            key,
+            value,
+        }) => {
+            assert_eq!(
+                (element.as_str(), key.as_str(), value.as_str()),
+                ("rom", "crc", "beef")
+            )
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(parse("deadbeef00", true).is_err());
+    assert_eq!(parse("beef", false).unwrap(), None);
+    assert_eq!(parse("deadbeef00", false).unwrap(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mixed_case_enums() {
+    let input = r#"
+<datafile>
+    <header>
+        <clrmamepro forcemerging="Full" forcepacking="ZIP"/>
+    </header>
+    <game name="Name">
+        <rom name="Name1" size="1" status="BadDump"/>
+        <rom name="Name2" size="1" status="NODUMP"/>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let clr_mame_pro = data_file.header.unwrap().clr_mame_pro.unwrap();
+    assert_eq!(clr_mame_pro.force_merging, ForceMerging::Full);
+    assert_eq!(clr_mame_pro.force_packing, ForcePacking::Zip);
+    let roms = &data_file.games[0].roms;
+    assert_eq!(roms[0].status, Status::BadDump);
+    assert_eq!(roms[1].status, Status::NoDump);
+    let result = DatReader::from_string(
+        r#"<datafile><header><clrmamepro forcemerging="Bogus"/></header></datafile>"#,
+    )
+    .read_all();
+    assert!(matches!(
+        result,
+        Err(DatReaderError::InvalidAttributeValue { .. })
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_other_status() {
+    let input = r#"<datafile><game name="Name"><rom name="Name" size="1" status="unknownvalue"/></game></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(
+        data_file.games[0].roms[0].status,
+        Status::Other("unknownvalue".to_owned())
+    );
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"status="unknownvalue""#));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_parse_warnings() {
+    let input = r#"<datafile><game name="Name" foo="1"><rom name="Name" bar="2"/><video/></game></datafile>"#;
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict(false);
+    let (data_file, warnings) = reader.read_all_with_warnings().unwrap();
+    assert_eq!(data_file.games[0].roms.len(), 1);
+    assert_eq!(warnings.len(), 3);
+    assert!(matches!(
+        &warnings[0],
+        ParseWarning::UnexpectedAttribute { key, tag, .. } if key == "foo" && tag == "game"
+    ));
+    assert!(matches!(
+        &warnings[1],
+        ParseWarning::UnexpectedAttribute { key, value, tag, .. }
+            if key == "bar" && value == "2" && tag == "rom"
+    ));
+    assert!(matches!(
+        &warnings[2],
+        ParseWarning::UnexpectedElement { tag, parent, .. } if tag == "video" && parent == "game"
+    ));
+
+    let input = r#"<datafile><game name="Name"><rom name="Name" size="big" crc="00000001"/></game></datafile>"#;
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict(false);
+    let (data_file, warnings) = reader.read_all_with_warnings().unwrap();
+    assert_eq!(data_file.games[0].roms[0].size, None);
+    assert_eq!(data_file.games[0].roms[0].crc, Some(Crc32(1)));
+    assert!(matches!(
+        &warnings[..],
+        [ParseWarning::InvalidAttributeValue { key, value, tag, .. }]
+            if key == "size" && value == "big" && tag == "rom"
+    ));
+
+    let (_, warnings) = DatReader::from_string(FULL_PARSE_INPUT)
+        .read_all_with_warnings()
+        .unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_clrmamepro_attributes() {
+    let input = r#"
+<datafile>
+    <header>
+        <clrmamepro header="No-Intro_NES.xml" forcemerging="split" forcenodump="required" forcepacking="zip" version="1.0" date="2022-01-01" comment="Comment"/>
+    </header>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let clr_mame_pro = data_file
+        .header
+        .as_ref()
+        .unwrap()
+        .clr_mame_pro
+        .as_ref()
+        .unwrap();
+    assert_eq!(
+        clr_mame_pro,
+        &ClrMamePro {
+            header: "No-Intro_NES.xml".to_owned(),
+            force_merging: ForceMerging::Split,
+            force_no_dump: ForceNoDump::Required,
+            force_packing: ForcePacking::Zip,
+            version: "1.0".to_owned(),
+            date: "2022-01-01".to_owned(),
+            comment: "Comment".to_owned(),
+            unknown: UnknownContent::default(),
+        }
+    );
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+    let result =
+        DatReader::from_string(r#"<datafile><header><clrmamepro bogus="1"/></header></datafile>"#)
+            .read_all();
+    assert!(matches!(
+        result,
+        Err(DatReaderError::UnexpectedAttribute(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_utf8_bom() {
+    let mut input = b"\xef\xbb\xbf".to_vec();
+    input.extend_from_slice(FULL_PARSE_INPUT.trim_start().as_bytes());
+    let expected = DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap();
+    let data_file = DatReader::from_reader(&input[..]).read_all().unwrap();
+    assert_eq!(data_file, expected);
+    let input = "\u{feff}<datafile><game name=\"X\"/></datafile>";
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.games[0].name, "X");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_no_intro_game_ids() {
+    let input = r#"<datafile><game name="Clone" id="0001" cloneofid="0000"/></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let game = &data_file.games[0];
+    assert_eq!(game.id, "0001");
+    assert_eq!(game.clone_of_id, "0000");
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"<game id="0001" name="Clone" cloneofid="0000"/>"#));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_sha256() {
+    let parse = |sha256: &str| {
+        let input = format!(
+            r#"<datafile><game name="Name"><rom name="Name" sha256="{}"/></game></datafile>"#,
+            sha256
+        );
+        DatReader::from_string(&input)
+            .read_all()
+            .map(|data_file| data_file.games[0].roms[0].sha256)
+    };
+    let sha256 = parse("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08").unwrap();
+    assert_eq!(sha256.unwrap().0[0], 0x9f);
+    assert!(matches!(
+        parse("9f86d081884c7d659a2feaa0c55ad015"),
+        Err(DatReaderError::InvalidAttributeValue { .. })
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_register_rom_attr() {
+    use std::sync::{Arc, Mutex};
+
+    let input = r#"
+<datafile>
+    <game name="A">
+        <rom name="a1" dumper="yes"/>
+        <rom name="a2"/>
+    </game>
+    <game name="B" dumper="no">
+        <rom name="b1" dumper="no"/>
+    </game>
+</datafile>"#;
+    let dumpers = Arc::new(Mutex::new(Vec::new()));
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict(false);
+    let captured = dumpers.clone();
+    reader.register_rom_attr("dumper", move |name, value, index| {
+        captured
+            .lock()
+            .unwrap()
+            .push((name.to_owned(), value.to_owned(), index))
+    });
+    let (data_file, warnings) = reader.read_all_with_warnings().unwrap();
+    assert_eq!(data_file.games[1].roms[0].name, "b1");
+    assert_eq!(
+        *dumpers.lock().unwrap(),
+        vec![
+            ("dumper".to_owned(), "yes".to_owned(), 0),
+            ("dumper".to_owned(), "no".to_owned(), 2),
+        ]
+    );
+    // Only rom attributes go to the handler
+    assert!(matches!(
+        &warnings[..],
+        [ParseWarning::UnexpectedAttribute { tag, .. }] if tag == "game"
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_bios() {
+    let input =
+        r#"<datafile><game name="Name"><rom name="x" size="1" bios="eu"/></game></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.games[0].roms[0].bios, "eu");
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"<rom name="x" size="1" bios="eu" status="good"/>"#));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_header_ref() {
+    let input = r#"<datafile><game name="Name"><rom name="Name.nes" size="16400" header="NES.xml"/></game></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.games[0].roms[0].header_ref, "NES.xml");
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"header="NES.xml""#));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_no_intro_rom_region_flags() {
+    let input = r#"<datafile><game name="Name"><rom name="Name.nes" size="40976" crc="3fe272fb" region="USA" flags="verified"/></game></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let rom = &data_file.games[0].roms[0];
+    assert_eq!(rom.region, "USA");
+    assert_eq!(rom.flags, "verified");
+    assert_eq!(rom.status, Status::Good);
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"region="USA" flags="verified""#));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_doctype() {
+    let mut reader = DatReader::from_string(FULL_PARSE_INPUT);
+    assert_eq!(
+        reader.doctype().unwrap(),
+        Some(
+            r#"datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd""#
+        )
+    );
+    assert_eq!(reader.read_all().unwrap().games.len(), 2);
+
+    let read = |input: &str| {
+        let mut reader = DatReader::from_string(input);
+        reader.set_require_logiqx_doctype(true);
+        reader.read_all()
+    };
+    assert!(read(FULL_PARSE_INPUT).is_ok());
+    assert!(read("<datafile/>").is_ok());
+    assert!(matches!(
+        read("<!DOCTYPE mame [<!ELEMENT mame (machine+)>]><datafile/>"),
+        Err(DatReaderError::UnexpectedDocType(_))
+    ));
+    let mut reader = DatReader::from_string("<datafile/>");
+    assert_eq!(reader.doctype().unwrap(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mame_driver_status_parse() {
+    assert_eq!("good".parse(), Ok(DriverStatus::Good));
+    assert_eq!("imperfect".parse(), Ok(DriverStatus::Imperfect));
+    assert_eq!("preliminary".parse(), Ok(DriverStatus::Preliminary));
+    assert!("baddump".parse::<DriverStatus>().is_err());
+    assert!("".parse::<DriverStatus>().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reuse_buf() {
+    let second = r#"<datafile><game name="X"><rom name="x.bin" size="1"/></game></datafile>"#;
+    let (first_result, buf) =
+        DatReader::from_reader_with_buf(FULL_PARSE_INPUT.as_bytes(), Vec::new())
+            .read_all_keep_buf();
+    assert!(buf.capacity() > 0);
+    let (second_result, _) =
+        DatReader::from_reader_with_buf(second.as_bytes(), buf).read_all_keep_buf();
+    assert_eq!(
+        first_result.unwrap(),
+        DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap()
+    );
+    assert_eq!(
+        second_result.unwrap(),
+        DatReader::from_string(second).read_all().unwrap()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_malformed_attribute() {
+    let result = DatReader::from_string(r#"<datafile><game name="X" year=1990></game></datafile>"#)
+        .read_all();
+    match result {
+        Err(DatReaderError::MalformedAttribute(msg)) => {
+            assert!(
+                msg.contains("attribute \"year\" in element \"game\""),
+                "{}",
+                msg
+            )
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_lenient_bools() {
+    let read = |value: &str, lenient: bool| {
+        let xml = format!(
+            r#"<datafile><game name="X"><release name="X" region="EUR" default="{}"/></game></datafile>"#,
+            value
+        );
+        let mut reader = DatReader::from_string(&xml);
+        reader.set_lenient_bools(lenient);
+        reader
+            .read_all()
+            .map(|data_file| data_file.games[0].releases[0].default)
+    };
+    for (value, expected) in [
+        ("yes", true),
+        ("no", false),
+        ("", false),
+        ("0", false),
+        ("1", true),
+        ("false", false),
+        ("true", true),
+    ] {
+        assert_eq!(read(value, true).unwrap(), expected, "{:?}", value);
+    }
+    for value in ["", "0", "1", "false", "true"] {
+        assert!(read(value, false).is_err(), "{:?}", value);
+    }
+    assert!(read("maybe", true).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_into_games() {
+    let data_file = DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap();
+    let names = data_file
+        .clone()
+        .into_games()
+        .map(|game| game.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Name", "Name2"]);
+    assert_eq!(data_file.clone().into_header(), data_file.header);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_no_header() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <!-- no header -->
+    <game name="Game 1">
+        <rom name="Game 1.bin" size="1"/>
+    </game>
+    <machine name="Game 2"/>
+</datafile>"#;
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict(true);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(data_file.header, None);
+    assert_eq!(data_file.games.len(), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_text_element_forms() {
+    let input = r#"
+<datafile>
+    <game name="Game 1">
+        <description/>
+        <year>   </year>
+        <manufacturer></manufacturer>
+        <comment><![CDATA[a <b> & c]]></comment>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let game = &data_file.games[0];
+    assert_eq!(game.description, "");
+    assert_eq!(game.year, "");
+    assert_eq!(game.manufacturer, "");
+    assert_eq!(game.comments, ["a <b> & c"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mame_root() {
+    let input = r#"
+<?xml version="1.0"?>
+<mame build="0.250 (mame0250)" debug="no">
+    <machine name="pacman" sourcefile="pacman/pacman.cpp">
+        <description>Pac-Man (Midway)</description>
+    </machine>
+</mame>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.element_kind, DataFileElementKind::Mame);
+    assert_eq!(data_file.build, "0.250 (mame0250)");
+    assert!(!data_file.debug);
+    assert_eq!(data_file.games[0].element_kind, GameElementKind::Machine);
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains("<mame build="));
+    assert!(!output.contains("DOCTYPE"));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_skip_bad_games() {
+    let input = r#"
+<datafile>
+    <game name="Game 1">
+        <rom name="Game 1.bin" size="1"/>
+    </game>
+    <game name="Game 2">
+        <description>Broken</description>
+        <rom name="Game 2.bin" size="1">
+            <bogus><nested/></bogus>
+            <also/>
+        </rom>
+        <rom name="Game 2.sav" size="1"/>
+    </game>
+    <game name="Game 3" year=1990>
+        <rom name="Game 3.bin" size="1"/>
+    </game>
+    <game name="Game 4">
+        <rom name="Game 4.bin" size="1"/>
+    </game>
+</datafile>"#;
+    assert!(DatReader::from_string(input).read_all().is_err());
+    let mut reader = DatReader::from_string(input);
+    reader.set_recovery_mode(RecoveryMode::SkipBadGames);
+    let (data_file, warnings) = reader.read_all_with_warnings().unwrap();
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| game.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Game 1", "Game 4"]);
+    let skipped = warnings
+        .iter()
+        .map(|warning| match warning {
+            ParseWarning::SkippedGame { name, .. } => name.as_str(),
+            other => panic!("unexpected warning {:?}", other),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(skipped, ["Game 2", "Game 3"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_trim_text() {
+    let input = r#"<datafile>
+    <game name="Game">
+        <comment>  indented
+  comment  </comment>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.games[0].comments, ["indented\n  comment"]);
+    let mut reader = DatReader::from_string(input);
+    reader.set_trim_text(false);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(data_file.games[0].comments, ["  indented\n  comment  "]);
+
+    let mut reader = DatReader::from_string(FULL_PARSE_INPUT);
+    reader.set_trim_text(false);
+    assert_eq!(
+        reader.read_all().unwrap(),
+        DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_preserve_comments() {
+    let input = r#"
+<!-- outside -->
+<datafile>
+    <!-- generated by hand -->
+    <game name="Game">
+        <!-- verified -->
+        <rom name="Game.bin" size="1"/>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert!(data_file.unknown.comments.is_empty());
+    let mut reader = DatReader::from_string(input);
+    reader.set_preserve_comments(true);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(data_file.unknown.comments, [" generated by hand "]);
+    assert_eq!(data_file.games[0].unknown.comments, [" verified "]);
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    let mut reader = DatReader::from_string(&output);
+    reader.set_preserve_comments(true);
+    assert_eq!(reader.read_all().unwrap(), data_file);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_invalid_names() {
+    let write = |tag: &str, key: &str| {
+        let mut data_file = DataFile::default();
+        data_file.unknown.elements.push(UnknownElement {
+            tag: tag.to_owned(),
+            attrs: vec![(key.to_owned(), "1".to_owned())],
+            ..Default::default()
+        });
+        DatWriter::to_string(&data_file)
+    };
+    assert!(write("x-tool:data_1.0", "é").is_ok());
+    for (tag, key) in [
+        (r#"x/><game name="Injected"/><y"#, "a"),
+        ("", "a"),
+        ("1x", "a"),
+        ("x", r#"a="1" b"#),
+        ("x", "-a"),
+    ] {
+        assert!(matches!(
+            write(tag, key),
+            Err(DatWriterError::InvalidName(_))
+        ));
+    }
+    let mut data_file = DataFile::default();
+    data_file
+        .unknown
+        .attrs
+        .push((r#"a="1" b"#.to_owned(), "2".to_owned()));
+    assert!(matches!(
+        DatWriter::to_string(&data_file),
+        Err(DatWriterError::InvalidName(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_invalid_comments() {
+    let write = |comment: &str| {
+        let mut data_file = DataFile::default();
+        data_file.games.push(Game {
+            name: "Game".to_owned(),
+            ..Default::default()
+        });
+        data_file.games[0].unknown.comments.push(comment.to_owned());
+        DatWriter::to_string(&data_file)
+    };
+    assert!(write(" a - b ").is_ok());
+    for comment in [
+        r#"x --> <game name="Injected"/> <!-- y"#,
+        "a--b",
+        "trailing -",
+    ] {
+        assert!(matches!(
+            write(comment),
+            Err(DatWriterError::InvalidComment(_))
+        ));
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_preserve_processing_instructions() {
+    let input = r#"
+<datafile>
+    <header>
+        <name>Name</name>
+    </header>
+    <?dat-tool mode="merge" level=2?>
+    <game name="Game">
+        <rom name="Game.bin" size="1"/>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert!(data_file.unknown.processing_instructions.is_empty());
+    let mut reader = DatReader::from_string(input);
+    reader.set_preserve_processing_instructions(true);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(
+        data_file.unknown.processing_instructions,
+        [r#"dat-tool mode="merge" level=2"#]
+    );
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"<?dat-tool mode="merge" level=2?>"#));
+    let mut reader = DatReader::from_string(&output);
+    reader.set_preserve_processing_instructions(true);
+    assert_eq!(reader.read_all().unwrap(), data_file);
+
+    let mut data_file = DataFile::default();
+    data_file
+        .unknown
+        .processing_instructions
+        .push(r#"x ?><game name="Injected"/><?y"#.to_owned());
+    assert!(matches!(
+        DatWriter::to_string(&data_file),
+        Err(DatWriterError::InvalidProcessingInstruction(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_strict_toplevel() {
+    let input = r#"
+<junk version="1"><data/></junk>
+<datafile>
+    <game name="Game"/>
+</datafile>"#;
+    assert!(matches!(
+        DatReader::from_string(input).read_all(),
+        Err(DatReaderError::UnexpectedElement(_))
+    ));
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict_toplevel(false);
+    let (data_file, warnings) = reader.read_all_with_warnings().unwrap();
+    assert_eq!(data_file.games[0].name, "Game");
+    assert!(matches!(
+        &warnings[..],
+        [ParseWarning::UnexpectedElement { tag, .. }] if tag == "junk"
+    ));
+
+    let mut reader =
+        DatReader::from_string(r#"<junk/><datafile><game name="Game" bogus="1"/></datafile>"#);
+    reader.set_strict_toplevel(false);
+    assert!(matches!(
+        reader.read_all(),
+        Err(DatReaderError::UnexpectedAttribute(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_bool_style() {
+    let data_file = DataFile {
+        debug: false,
+        games: vec![Game {
+            name: "Game".to_owned(),
+            is_bios: true,
+            roms: vec![
+                Rom {
+                    name: "a.bin".to_owned(),
+                    ..Default::default()
+                },
+                Rom {
+                    name: "b.bin".to_owned(),
+                    mia: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let write = |bool_style: BoolStyle| {
+        let mut writer = DatWriter::from_writer(Vec::new());
+        writer.set_bool_style(bool_style);
+        String::from_utf8(writer.write_all(&data_file).unwrap()).unwrap()
+    };
+    let output = write(BoolStyle::OmitFalse);
+    assert!(output.contains("<datafile>"));
+    assert!(output.contains(r#"<game name="Game" isbios="yes">"#));
+    assert!(output.contains(r#"<rom name="a.bin" status="good"/>"#));
+    assert!(output.contains(r#"<rom name="b.bin" status="good" mia="yes"/>"#));
+    assert_eq!(output, DatWriter::to_string(&data_file).unwrap());
+    let output = write(BoolStyle::YesNo);
+    assert!(output.contains(r#"<datafile debug="no">"#));
+    assert!(output.contains(r#"<game name="Game" isbios="yes">"#));
+    assert!(output.contains(r#"<rom name="a.bin" status="good" mia="no"/>"#));
+    assert!(output.contains(r#"<rom name="b.bin" status="good" mia="yes"/>"#));
+    for output in [write(BoolStyle::OmitFalse), write(BoolStyle::YesNo)] {
+        assert_eq!(
+            DatReader::from_string(&output).read_all().unwrap(),
+            data_file
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_unexpanded_empty_elements() {
+    let read = |input: &str, configure: &dyn Fn(&mut DatReader<&[u8]>)| {
+        let mut reader = DatReader::from_string(input);
+        reader.reader.config_mut().expand_empty_elements = false;
+        configure(&mut reader);
+        reader.read_all_with_warnings()
+    };
+    let expected = DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap();
+    assert_eq!(read(FULL_PARSE_INPUT, &|_| ()).unwrap().0, expected);
+    assert_eq!(read("<datafile/>", &|_| ()).unwrap().0, DataFile::default());
+
+    let input = r#"
+<junk/>
+<datafile>
+    <game name="Game 1">
+        <bogus/>
+        <extra key="value"/>
+        <rom name="Game 1.bin"/>
+    </game>
+    <game name="Game 2" bogus="1"/>
+    <game name="Game 3"><rom name="Game 3.bin" size="1"/></game>
+</datafile>"#;
+    let (data_file, warnings) = read(input, &|reader| {
+        reader.set_strict(false);
+        reader.set_recovery_mode(RecoveryMode::SkipBadGames);
+    })
+    .unwrap();
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| game.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Game 1", "Game 2", "Game 3"]);
+    assert_eq!(warnings.len(), 4);
+
+    let (data_file, _) = read(input, &|reader| {
+        reader.set_strict_toplevel(false);
+        reader.set_preserve_unknown(true);
+        reader.set_recovery_mode(RecoveryMode::SkipBadGames);
+    })
+    .unwrap();
+    let game = &data_file.games[0];
+    assert_eq!(game.unknown.elements[1].tag, "extra");
+    assert_eq!(game.roms[0].name, "Game 1.bin");
+    assert_eq!(data_file.games[1].unknown.attrs.len(), 1);
+
+    let result = read(input, &|reader| {
+        reader.set_strict_toplevel(false);
+        reader.set_preserve_unknown(true);
+        reader.set_require_rom_fields(true);
+        reader.set_recovery_mode(RecoveryMode::SkipBadGames);
+    });
+    let names = result
+        .unwrap()
+        .0
+        .games
+        .iter()
+        .map(|game| game.name.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Game 2", "Game 3"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_omit_empty() {
+    let data_file = DataFile {
+        games: vec![Game {
+            name: "Game".to_owned(),
+            description: "Description".to_owned(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(
+        r#"
+    <game name="Game">
+        <description>Description</description>
+    </game>
+"#
+    ));
+    let mut writer = DatWriter::from_writer(Vec::new());
+    writer.set_omit_empty(false);
+    let output = String::from_utf8(writer.write_all(&data_file).unwrap()).unwrap();
+    assert!(output.contains(r#"<game id="" name="Game" sourcefile="" cloneof="""#));
+    assert!(output.contains("<year/>"));
+    assert!(output.contains("<manufacturer/>"));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_read_into() {
+    let mut data_file = DataFile::default();
+    DatReader::from_string(
+        r#"<datafile><header><name>First</name></header><game name="A"/></datafile>"#,
+    )
+    .read_into(&mut data_file)
+    .unwrap();
+    DatReader::from_string(
+        r#"<datafile build="2"><header><name>Second</name></header><game name="B"/><game name="C"/></datafile>"#,
+    )
+    .read_into(&mut data_file)
+    .unwrap();
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| game.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["A", "B", "C"]);
+    assert_eq!(data_file.header.unwrap().name, "First");
+    assert_eq!(data_file.build, "");
+
+    let mut data_file = DataFile::default();
+    assert!(DatReader::from_string(r#"<datafile><game name="A">"#)
+        .read_into(&mut data_file)
+        .is_err());
+    assert_eq!(data_file, DataFile::default());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_invalid_attribute_value() {
+    let read = |input: &str| DatReader::from_string(input).read_all().unwrap_err();
+    let err = read(r#"<datafile><header><clrmamepro forcemerging="weird"/></header></datafile>"#);
+    match &err {
+        DatReaderError::InvalidAttributeValue {
+            element,
+            key,
+            value,
+        } => assert_eq!(
+            (element.as_str(), key.as_str(), value.as_str()),
+            ("clrmamepro", "forcemerging", "weird")
+        ),
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        r#"Invalid value "weird" for attribute "forcemerging" in element "clrmamepro""#
+    );
+    assert!(err.is_schema_error());
+    assert!(matches!(
+        read(r#"<datafile><header><clrmamepro weird="none"/></header></datafile>"#),
+        DatReaderError::UnexpectedAttribute(_)
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_max_depth() {
+    let nested = format!(
+        "<datafile><game name=\"Name\">{}{}</game></datafile>",
+        "<x>".repeat(100_000),
+        "</x>".repeat(100_000)
+    );
+    let mut reader = DatReader::from_string(&nested);
+    reader.set_preserve_unknown(true);
+    assert!(matches!(
+        reader.read_all(),
+        Err(DatReaderError::MaxDepthExceeded(_))
+    ));
+
+    let mut reader = DatReader::from_string(FULL_PARSE_INPUT);
+    reader.set_max_depth(3);
+    assert!(reader.read_all().is_ok());
+    let mut reader = DatReader::from_string(FULL_PARSE_INPUT);
+    reader.set_max_depth(2);
+    assert!(matches!(
+        reader.read_all(),
+        Err(DatReaderError::MaxDepthExceeded(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_max_value_len() {
+    let read = |input: &str| {
+        let mut reader = DatReader::from_string(input);
+        reader.set_preserve_unknown(true);
+        reader.set_max_value_len(4);
+        reader.read_all()
+    };
+    assert!(read(r#"<datafile><game name="Name"/></datafile>"#).is_ok());
+    assert!(matches!(
+        read(r#"<datafile><game name="Name1"/></datafile>"#),
+        Err(DatReaderError::ValueTooLong(_))
+    ));
+    assert!(matches!(
+        read(r#"<datafile><game name="a"><description>Long</description></game></datafile>"#)
+            .map(|data_file| data_file.games[0].description.clone()),
+        Ok(description) if description == "Long"
+    ));
+    assert!(matches!(
+        read(r#"<datafile><game name="a"><description>Longer</description></game></datafile>"#),
+        Err(DatReaderError::ValueTooLong(_))
+    ));
+    assert!(matches!(
+        read(r#"<datafile><game name="a"><x y="value"/></game></datafile>"#),
+        Err(DatReaderError::ValueTooLong(_))
+    ));
+    assert!(matches!(
+        read(r#"<datafile><game name="a"><x>text</x><y>texts</y></game></datafile>"#),
+        Err(DatReaderError::ValueTooLong(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error_classification() {
+    let read = |input: &str| DatReader::from_string(input).read_all().unwrap_err();
+    let eof = read(r#"<datafile><game name="Game">"#);
+    assert!(eof.is_eof() && !eof.is_schema_error() && !eof.is_xml_error());
+    let truncated_tag = read(r#"<datafile><game name="Ga"#);
+    assert!(truncated_tag.is_eof() && truncated_tag.is_xml_error());
+    let schema = read(r#"<datafile><game name="Game" bogus="1"/></datafile>"#);
+    assert!(schema.is_schema_error() && !schema.is_eof() && !schema.is_xml_error());
+    let schema = read(r#"<datafile><bogus/></datafile>"#);
+    assert!(schema.is_schema_error());
+    let xml = read(r#"<datafile><game name="Game"></rom></datafile>"#);
+    assert!(xml.is_xml_error() && !xml.is_eof() && !xml.is_schema_error());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_rom_mia() {
+    let input = r#"<datafile><game name="Game"><rom name="a.bin" size="1" mia="yes"/><rom name="b.bin" size="1" mia="no"/><rom name="c.bin" size="1"/></game></datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let mia = data_file.games[0]
+        .roms
+        .iter()
+        .map(|rom| rom.mia)
+        .collect::<Vec<_>>();
+    assert_eq!(mia, [true, false, false]);
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert_eq!(output.matches("mia=").count(), 1);
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_streaming_writer() {
+    let header = Header {
+        name: "Name".to_owned(),
+        ..Header::default()
+    };
+    let games = [
+        Game::builder("Game 1")
+            .rom(Rom::builder("Game 1.bin").size(1).build().unwrap())
+            .build()
+            .unwrap(),
+        Game::builder("Game 2").build().unwrap(),
+    ];
+    let mut writer = DatWriter::from_writer(Vec::new()).start(&header).unwrap();
+    for game in &games {
+        writer.write_game(game).unwrap();
+    }
+    let output = writer.finish().unwrap();
+    let data_file = DatReader::from_bytes(&output).read_all().unwrap();
+    assert_eq!(data_file.header.as_ref(), Some(&header));
+    assert_eq!(data_file.games, games);
+
+    let output = DatWriter::from_writer(Vec::new())
+        .start(&header)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let data_file = DatReader::from_bytes(&output).read_all().unwrap();
+    assert_eq!(data_file.header.as_ref(), Some(&header));
+    assert!(data_file.games.is_empty());
+
+    // Streaming honors the writer's options
+    let configure = || {
+        let mut writer = DatWriter::from_writer(Vec::new());
+        writer.set_bool_style(BoolStyle::YesNo);
+        writer.set_omit_empty(false);
+        writer
+    };
+    let mut writer = configure().start(&header).unwrap();
+    for game in &games {
+        writer.write_game(game).unwrap();
+    }
+    let streamed = writer.finish().unwrap();
+    let data_file = DataFile {
+        header: Some(header),
+        games: games.to_vec(),
+        ..DataFile::default()
+    };
+    let written = configure().write_all(&data_file).unwrap();
+    // The root element has no attributes to write when streaming
+    let after_root = |output: Vec<u8>| {
+        let output = String::from_utf8(output).unwrap();
+        output[output.find("<header>").unwrap()..].to_owned()
+    };
+    assert_eq!(after_root(streamed), after_root(written));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_duplicate_attrs() {
+    let input = r#"<datafile><game name="a"><rom name="a" size="1" name="b"/></game></datafile>"#;
+    match DatReader::from_string(input).read_all() {
+        Err(DatReaderError::MalformedAttribute(msg)) => {
+            assert!(msg.contains("\"name\" in element \"rom\""), "{}", msg)
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    let mut reader = DatReader::from_string(input);
+    reader.set_reject_duplicate_attrs(false);
+    assert_eq!(reader.read_all().unwrap().games[0].roms[0].name, "b");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_data_file_from_str() {
+    let data_file: DataFile = FULL_PARSE_INPUT.parse().unwrap();
+    assert_eq!(
+        data_file,
+        DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap()
+    );
+    assert!("<datafile><bogus/></datafile>".parse::<DataFile>().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_progress_callback() {
+    use std::sync::{Arc, Mutex};
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let mut reader = DatReader::from_string(FULL_PARSE_INPUT);
+    let captured = progress.clone();
+    reader.set_progress_callback(move |info| captured.lock().unwrap().push(info));
+    reader.read_all().unwrap();
+    let progress = progress.lock().unwrap();
+    assert_eq!(
+        progress.iter().map(|info| info.games).collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(progress[0].position < progress[1].position);
+    assert!(progress[1].position < FULL_PARSE_INPUT.len() as u64);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_status_quality_rank() {
+    let mut statuses = [
+        Status::Verified,
+        Status::NoDump,
+        Status::Good,
+        Status::Other("unknownvalue".to_owned()),
+        Status::BadDump,
+    ];
+    statuses.sort_by_key(Status::quality_rank);
+    assert_eq!(
+        statuses,
+        [
+            Status::NoDump,
+            Status::BadDump,
+            Status::Other("unknownvalue".to_owned()),
+            Status::Good,
+            Status::Verified,
+        ]
+    );
+    assert!(Status::BadDump.quality_rank() > Status::NoDump.quality_rank());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_machine_element() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <machine name="puckman" sourcefile="pacman.cpp">
+        <description>PuckMan (Japan set 1)</description>
+        <year>1980</year>
+        <manufacturer>Namco</manufacturer>
+        <rom name="pm1_prg1.6e" size="2048" crc="f36e88ab"/>
+    </machine>
+    <game name="Name">
+        <description>Description</description>
+    </game>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.games.len(), 2);
+    let machine = &data_file.games[0];
+    assert_eq!(machine.element_kind, GameElementKind::Machine);
+    assert_eq!(machine.name, "puckman");
+    assert_eq!(machine.manufacturer, "Namco");
+    assert_eq!(machine.roms[0].crc, Some(Crc32(0xf36e88ab)));
+    assert_eq!(data_file.games[1].element_kind, GameElementKind::Game);
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains("<machine name=\"puckman\""));
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mame_driver() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <machine name="puckman">
+        <description>PuckMan (Japan set 1)</description>
+        <device_ref name="z80"/>
+        <device_ref name="namco"/>
+        <driver status="imperfect" emulation="good" savestate="supported"/>
+    </machine>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    let machine = &data_file.games[0];
+    assert_eq!(
+        machine.device_refs,
+        vec![
+            DeviceRef {
+                name: "z80".to_owned(),
+                unknown: UnknownContent::default(),
+            },
+            DeviceRef {
+                name: "namco".to_owned(),
+                unknown: UnknownContent::default(),
+            }
+        ]
+    );
+    assert_eq!(
+        machine.driver,
+        Some(Driver {
+            status: DriverStatus::Imperfect,
+            emulation: DriverStatus::Good,
+            save_state: SaveState::Supported,
+            unknown: UnknownContent::default(),
+        })
+    );
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_preserve_unknown() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile vendor="Vendor">
+    <game name="Name" region="Europe">
+        <description>Description</description>
+        <rom name="Name" size="16" dumper="x"/>
+        <extension kind="test">
+            Text
+            <child key="value"/>
+        </extension>
+    </game>
+</datafile>"#;
+    let mut reader = DatReader::from_string(input);
+    reader.set_preserve_unknown(true);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(
+        data_file.unknown.attrs,
+        vec![("vendor".to_owned(), "Vendor".to_owned())]
+    );
+    let game = &data_file.games[0];
+    assert_eq!(
+        game.unknown,
+        UnknownContent {
+            attrs: vec![("region".to_owned(), "Europe".to_owned())],
+            elements: vec![UnknownElement {
+                tag: "extension".to_owned(),
+                attrs: vec![("kind".to_owned(), "test".to_owned())],
+                text: "Text".to_owned(),
+                children: vec![UnknownElement {
+                    tag: "child".to_owned(),
+                    attrs: vec![("key".to_owned(), "value".to_owned())],
+                    ..UnknownElement::default()
+                }],
+            }],
+            ..UnknownContent::default()
+        }
+    );
+    assert_eq!(
+        game.roms[0].unknown.attrs,
+        vec![("dumper".to_owned(), "x".to_owned())]
+    );
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    let mut reader = DatReader::from_string(&output);
+    reader.set_preserve_unknown(true);
+    assert_eq!(reader.read_all().unwrap(), data_file);
+
+    let mut reader = DatReader::from_string(input);
+    reader.set_strict(false);
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(data_file.games[0].unknown, UnknownContent::default());
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+#[test]
+fn test_serde_json_round_trip() {
+    let data_file = DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap();
+    let json = serde_json::to_value(&data_file).unwrap();
+    assert_eq!(json["debug"], serde_json::Value::Bool(true));
+    assert_eq!(json["header"]["clr_mame_pro"]["force_merging"], "full");
+    assert_eq!(json["games"][0]["roms"][0]["status"], "baddump");
+    assert_eq!(json["games"][0]["roms"][0]["crc"], "0123abcd");
+    assert_eq!(json["games"][0]["roms"][1]["size"], 2048);
+    let reparsed: DataFile = serde_json::from_value(json).unwrap();
+    assert_eq!(reparsed, data_file);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reject_duplicates() {
+    let read = |input: &str| {
+        let mut reader = DatReader::from_string(input);
+        reader.set_reject_duplicates(true);
+        reader.read_all()
+    };
+    let result =
+        read(r#"<datafile><game name="X"/><game name="Y"/><machine name="X"/></datafile>"#);
+    match result {
+        Err(DatReaderError::DuplicateName(msg)) => assert!(msg.contains("\"X\"")),
+        other => panic!("unexpected result {:?}", other),
+    }
+    let result =
+        read(r#"<datafile><game name="X"><rom name="a"/><rom name="a"/></game></datafile>"#);
+    assert!(matches!(result, Err(DatReaderError::DuplicateName(_))));
+    let result = read(r#"<datafile><game name="X"/><game name="x"/></datafile>"#);
+    assert_eq!(result.unwrap().games.len(), 2);
+    let result = read(
+        r#"<datafile><game name="X"><rom name="a"/></game><game name="Y"><rom name="a"/></game></datafile>"#,
+    );
+    assert_eq!(result.unwrap().games.len(), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_require_rom_fields() {
+    let read = |rom: &str| {
+        let input = format!(r#"<datafile><game name="X">{}</game></datafile>"#, rom);
+        let mut reader = DatReader::from_string(&input);
+        reader.set_require_rom_fields(true);
+        reader.read_all()
+    };
+    match read(r#"<rom size="1024" crc="0123abcd"/>"#) {
+        Err(DatReaderError::MissingField(msg)) => assert!(msg.contains("\"name\"")),
+        other => panic!("unexpected result {:?}", other),
+    }
+    match read(r#"<rom name="a.bin" crc="0123abcd"/>"#) {
+        Err(DatReaderError::MissingField(msg)) => assert!(msg.contains("\"size\"")),
+        other => panic!("unexpected result {:?}", other),
+    }
+    let data_file = read(r#"<rom name="a.bin" status="nodump"/>"#).unwrap();
+    assert_eq!(data_file.games[0].roms[0].status, Status::NoDump);
+    assert!(read(r#"<rom name="a.bin" size="1024"/>"#).is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_windows_1252() {
+    let mut input = br#"<?xml version="1.0" encoding="windows-1252"?>
+<datafile>
+    <game name="Game">
+        <manufacturer>Soci"#
+        .to_vec();
+    input.push(0xe9);
+    input.extend_from_slice(b"t\xe9</manufacturer>\n    </game>\n</datafile>");
+    let mut reader = DatReader::from_reader(&input[..]);
+    assert_eq!(reader.encoding().unwrap(), "windows-1252");
+    let data_file = reader.read_all().unwrap();
+    assert_eq!(data_file.games[0].manufacturer, "Soci\u{e9}t\u{e9}");
+
+    let mut reader = DatReader::from_string(r#"<datafile><game name="X"/></datafile>"#);
+    assert_eq!(reader.encoding().unwrap(), "UTF-8");
+    assert_eq!(reader.read_all().unwrap().games[0].name, "X");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_bytes() {
+    assert_eq!(
+        DatReader::from_bytes(FULL_PARSE_INPUT.as_bytes())
+            .read_all()
+            .unwrap(),
+        DatReader::from_string(FULL_PARSE_INPUT).read_all().unwrap()
+    );
+    let input = b"<?xml version=\"1.0\" encoding=\"iso-8859-1\"?><datafile><game name=\"\xc4\"/></datafile>";
+    assert_eq!(
+        DatReader::from_bytes(input).read_all().unwrap().games[0].name,
+        "\u{c4}"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_no_intro_header() {
+    let input = r#"
+<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile id="49">
+    <header>
+        <id>49</id>
+        <name>Nintendo - Game Boy</name>
+        <description>Nintendo - Game Boy</description>
+        <version>20220101-000000</version>
+        <author>Author</author>
+        <homepage>No-Intro</homepage>
+        <url>https://www.no-intro.org</url>
+        <subset>Subset</subset>
+        <retool>Retool</retool>
+        <clrmamepro forcenodump="required"/>
+    </header>
+</datafile>"#;
+    let data_file = DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.id, "49");
+    let header = data_file.header.as_ref().unwrap();
+    assert_eq!(header.id, "49");
+    assert_eq!(header.name, "Nintendo - Game Boy");
+    assert_eq!(header.version, "20220101-000000");
+    assert_eq!(header.homepage, "No-Intro");
+    assert_eq!(header.url, "https://www.no-intro.org");
+    assert_eq!(header.subset, "Subset");
+    assert_eq!(header.retool, "Retool");
+    assert_eq!(
+        header.clr_mame_pro.as_ref().unwrap().force_no_dump,
+        ForceNoDump::Required
+    );
+
+    let output = DatWriter::to_string(&data_file).unwrap();
+    assert_eq!(
+        DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_release_date() {
+    let parse = |date: &str, strict: bool| {
+        let input = format!(
+            r#"<datafile><game name="Name"><release name="Name" region="USA" date="{}"/></game></datafile>"#,
+            date
+        );
+        let mut reader = DatReader::from_string(&input);
+        reader.set_strict(strict);
+        reader
+            .read_all()
+            .map(|data_file| data_file.games[0].releases[0].date.clone())
+    };
+    assert_eq!(parse("19960227", true).unwrap().year(), Some(1996));
+    assert_eq!(parse("1996-02-27", true).unwrap().day(), Some(27));
+    assert!(matches!(
+        parse("Spring 1996", true),
+        Err(DatReaderError::InvalidAttributeValue { .. })
+    ));
+    let date = parse("Spring 1996", false).unwrap();
+    assert_eq!(date.raw, "Spring 1996");
+    assert_eq!(date.parsed, None);
+}
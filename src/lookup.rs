@@ -0,0 +1,553 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+use crate::{Crc32, DataFile, Disk, Game, HashRef, Rom, Sha1, Status};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RomOrDisk<'a> {
+    Rom(&'a Game, &'a Rom),
+    Disk(&'a Game, &'a Disk),
+}
+
+impl<'a> RomOrDisk<'a> {
+    pub fn game(&self) -> &'a Game {
+        match self {
+            RomOrDisk::Rom(game, _) | RomOrDisk::Disk(game, _) => game,
+        }
+    }
+}
+
+/// Roms indexed by size and CRC, for repeated `find` lookups. Built with
+/// `DataFile::rom_index`.
+#[derive(Clone, Debug, Default)]
+pub struct RomIndex<'a> {
+    roms: HashMap<(u64, Crc32), Vec<(&'a Game, &'a Rom)>>,
+}
+
+impl<'a> RomIndex<'a> {
+    /// Finds non-nodump roms with the given size and CRC
+    pub fn find(&self, size: u64, crc: &Crc32) -> &[(&'a Game, &'a Rom)] {
+        self.roms
+            .get(&(size, *crc))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CloneChainError {
+    MissingGame(String),
+    Cycle(String),
+}
+
+impl Error for CloneChainError {}
+
+impl fmt::Display for CloneChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::CloneChainError::*;
+        match self {
+            MissingGame(name) => write!(f, "Missing game \"{}\"", name),
+            Cycle(name) => write!(f, "Clone cycle at game \"{}\"", name),
+        }
+    }
+}
+
+impl DataFile {
+    /// Returns the named game followed by each of its ancestors by `cloneof`, ending with a
+    /// parent.
+    pub fn clone_chain(&self, game_name: &str) -> Result<Vec<&Game>, CloneChainError> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut name = game_name;
+        loop {
+            let game = self
+                .game(name)
+                .ok_or_else(|| CloneChainError::MissingGame(name.to_owned()))?;
+            if !seen.insert(name) {
+                return Err(CloneChainError::Cycle(name.to_owned()));
+            }
+            chain.push(game);
+            if game.clone_of.is_empty() {
+                return Ok(chain);
+            }
+            name = &game.clone_of;
+        }
+    }
+    /// Finds the parent of a game by its `cloneof`, or `romof` if `cloneof` isn't set
+    pub fn parent_of(&self, game: &Game) -> Option<&Game> {
+        let parent = if game.clone_of.is_empty() {
+            &game.rom_of
+        } else {
+            &game.clone_of
+        };
+        if parent.is_empty() {
+            return None;
+        }
+        self.game(parent)
+    }
+    pub fn bios_games(&self) -> impl Iterator<Item = &Game> {
+        self.games.iter().filter(|game| game.is_bios)
+    }
+    pub fn regular_games(&self) -> impl Iterator<Item = &Game> {
+        self.games.iter().filter(|game| !game.is_bios)
+    }
+    /// Finds a game by exact name. If there are duplicates, the first one is returned.
+    pub fn game(&self, name: &str) -> Option<&Game> {
+        self.games.iter().find(|game| game.name == name)
+    }
+    pub fn game_mut(&mut self, name: &str) -> Option<&mut Game> {
+        self.games.iter_mut().find(|game| game.name == name)
+    }
+    pub fn index_by_crc(&self) -> HashMap<Crc32, Vec<(&Game, &Rom)>> {
+        let mut index: HashMap<Crc32, Vec<(&Game, &Rom)>> = HashMap::new();
+        for game in &self.games {
+            for rom in &game.roms {
+                if let Some(crc) = rom.crc {
+                    index.entry(crc).or_default().push((game, rom));
+                }
+            }
+        }
+        index
+    }
+    /// Lists every CRC shared by more than one rom, ordered by CRC
+    pub fn crc_collisions(&self) -> Vec<(Crc32, Vec<(&Game, &Rom)>)> {
+        let mut collisions = self
+            .index_by_crc()
+            .into_iter()
+            .filter(|(_, roms)| roms.len() > 1)
+            .collect::<Vec<_>>();
+        collisions.sort_by_key(|(crc, _)| *crc);
+        collisions
+    }
+    /// Finds non-nodump roms with the given size and CRC. This scans every rom, so for many
+    /// lookups build a `RomIndex` with `rom_index` instead.
+    pub fn find_rom(&self, size: u64, crc: &Crc32) -> Vec<(&Game, &Rom)> {
+        let mut result = Vec::new();
+        for game in &self.games {
+            for rom in &game.roms {
+                if rom.status != Status::NoDump
+                    && rom.size == Some(size)
+                    && rom.crc.as_ref() == Some(crc)
+                {
+                    result.push((game, rom));
+                }
+            }
+        }
+        result
+    }
+    /// Indexes the non-nodump roms that have both a size and a CRC
+    pub fn rom_index(&self) -> RomIndex<'_> {
+        let mut index = RomIndex::default();
+        for game in &self.games {
+            for rom in &game.roms {
+                if rom.status == Status::NoDump {
+                    continue;
+                }
+                if let (Some(size), Some(crc)) = (rom.size, rom.crc) {
+                    index.roms.entry((size, crc)).or_default().push((game, rom));
+                }
+            }
+        }
+        index
+    }
+    pub fn find_by_sha1(&self, sha1: &Sha1) -> Vec<RomOrDisk<'_>> {
+        let mut result = Vec::new();
+        for game in &self.games {
+            for rom in &game.roms {
+                if rom.status != Status::NoDump && rom.sha1.as_ref() == Some(sha1) {
+                    result.push(RomOrDisk::Rom(game, rom));
+                }
+            }
+            for disk in &game.disks {
+                if disk.status != Status::NoDump && disk.sha1.as_ref() == Some(sha1) {
+                    result.push(RomOrDisk::Disk(game, disk));
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Game {
+    /// True if the game is a clone of another game, either by name or by No-Intro id
+    pub fn is_clone(&self) -> bool {
+        !self.clone_of.is_empty() || !self.clone_of_id.is_empty()
+    }
+    pub fn is_parent(&self) -> bool {
+        !self.is_clone()
+    }
+    /// Picks a representative rom: the only one, or else the largest. Roms without a size
+    /// count as smaller than any sized rom, and ties go to the rom that comes first.
+    pub fn primary_rom(&self) -> Option<&Rom> {
+        self.roms.iter().rev().max_by_key(|rom| rom.size)
+    }
+    /// Returns the roms ordered by their `track` number, or `number` if `track` isn't set.
+    /// Roms with neither come last, and ties keep the document order.
+    pub fn tracks(&self) -> Vec<&Rom> {
+        let mut tracks: Vec<&Rom> = self.roms.iter().collect();
+        tracks.sort_by_key(|rom| {
+            let track = rom.track.or(rom.number);
+            (track.is_none(), track)
+        });
+        tracks
+    }
+    /// Every hash of the game's roms and disks, skipping nodump entries
+    pub fn hashes(&self) -> impl Iterator<Item = HashRef<'_>> {
+        let roms = self
+            .roms
+            .iter()
+            .filter(|rom| rom.status != Status::NoDump)
+            .flat_map(|rom| {
+                rom.crc
+                    .iter()
+                    .map(HashRef::Crc32)
+                    .chain(rom.sha1.iter().map(HashRef::Sha1))
+                    .chain(rom.sha256.iter().map(HashRef::Sha256))
+                    .chain(rom.md5.iter().map(HashRef::Md5))
+            });
+        let disks = self
+            .disks
+            .iter()
+            .filter(|disk| disk.status != Status::NoDump)
+            .flat_map(|disk| {
+                disk.sha1
+                    .iter()
+                    .map(HashRef::Sha1)
+                    .chain(disk.md5.iter().map(HashRef::Md5))
+            });
+        roms.chain(disks)
+    }
+}
+
+#[test]
+fn test_index_by_crc() {
+    let rom = |name: &str, crc: u32| Rom {
+        name: name.to_owned(),
+        crc: Some(Crc32(crc)),
+        ..Default::default()
+    };
+    let data_file = DataFile {
+        games: vec![
+            Game {
+                name: "Game1".to_owned(),
+                roms: vec![rom("Rom1", 0x12345678), rom("Rom2", 0xdeadbeef)],
+                ..Default::default()
+            },
+            Game {
+                name: "Game2".to_owned(),
+                roms: vec![rom("Rom3", 0x12345678), Rom::default()],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let index = data_file.index_by_crc();
+    assert_eq!(index.len(), 2);
+    let names = |crc: u32| {
+        index[&Crc32(crc)]
+            .iter()
+            .map(|(game, rom)| (game.name.as_str(), rom.name.as_str()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(0x12345678),
+        vec![("Game1", "Rom1"), ("Game2", "Rom3")]
+    );
+    assert_eq!(names(0xdeadbeef), vec![("Game1", "Rom2")]);
+}
+
+#[test]
+fn test_find_by_sha1() {
+    let sha1 = Sha1([0xaa; 20]);
+    let data_file = DataFile {
+        games: vec![
+            Game {
+                name: "Game1".to_owned(),
+                roms: vec![
+                    Rom {
+                        name: "Rom1".to_owned(),
+                        sha1: Some(sha1),
+                        ..Default::default()
+                    },
+                    Rom {
+                        name: "Rom2".to_owned(),
+                        status: Status::NoDump,
+                        ..Default::default()
+                    },
+                    Rom {
+                        name: "Rom3".to_owned(),
+                        sha1: Some(sha1),
+                        status: Status::NoDump,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            Game {
+                name: "Game2".to_owned(),
+                disks: vec![Disk {
+                    name: "Disk1".to_owned(),
+                    sha1: Some(sha1),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let result = data_file.find_by_sha1(&sha1);
+    assert_eq!(
+        result,
+        vec![
+            RomOrDisk::Rom(&data_file.games[0], &data_file.games[0].roms[0]),
+            RomOrDisk::Disk(&data_file.games[1], &data_file.games[1].disks[0]),
+        ]
+    );
+    assert_eq!(result[1].game().name, "Game2");
+    assert!(data_file.find_by_sha1(&Sha1([0xbb; 20])).is_empty());
+}
+
+#[test]
+fn test_game_lookup() {
+    let game = |name: &str, year: &str| Game {
+        name: name.to_owned(),
+        year: year.to_owned(),
+        ..Default::default()
+    };
+    let mut data_file = DataFile {
+        games: vec![game("A", "1990"), game("B", "1991"), game("A", "1992")],
+        ..Default::default()
+    };
+    assert_eq!(
+        data_file.game("B").map(|game| game.year.as_str()),
+        Some("1991")
+    );
+    assert_eq!(
+        data_file.game("A").map(|game| game.year.as_str()),
+        Some("1990")
+    );
+    assert!(data_file.game("a").is_none());
+    assert!(data_file.game("C").is_none());
+    data_file.game_mut("B").unwrap().year = "1999".to_owned();
+    assert_eq!(data_file.games[1].year, "1999");
+    assert!(data_file.game_mut("C").is_none());
+}
+
+#[test]
+fn test_find_rom() {
+    let input = r#"
+<datafile>
+    <game name="A">
+        <rom name="a.bin" size="1024" crc="deadbeef"/>
+        <rom name="a.sav" size="1024" crc="deadbeef" status="nodump"/>
+    </game>
+    <game name="B">
+        <rom name="b.bin" size="2048" crc="deadbeef"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let found = data_file.find_rom(1024, &Crc32(0xdeadbeef));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0.name, "A");
+    assert_eq!(found[0].1.name, "a.bin");
+    assert!(data_file.find_rom(4096, &Crc32(0xdeadbeef)).is_empty());
+    assert!(data_file.find_rom(1024, &Crc32(0x12345678)).is_empty());
+
+    let index = data_file.rom_index();
+    assert_eq!(index.find(1024, &Crc32(0xdeadbeef)), &found[..]);
+    assert_eq!(index.find(2048, &Crc32(0xdeadbeef))[0].0.name, "B");
+    assert!(index.find(4096, &Crc32(0xdeadbeef)).is_empty());
+    assert!(index.find(1024, &Crc32(0x12345678)).is_empty());
+}
+
+#[test]
+fn test_game_hashes() {
+    let input = r#"
+<datafile>
+    <game name="Game">
+        <rom name="a.bin" size="1" crc="00000001" md5="d41d8cd98f00b204e9800998ecf8427e"/>
+        <rom name="b.bin" size="1" crc="00000002" status="nodump"/>
+        <rom name="c.bin" size="1" crc="00000003"/>
+        <disk name="d" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let hashes = data_file.games[0]
+        .hashes()
+        .map(|hash| hash.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hashes,
+        [
+            "crc32:00000001",
+            "md5:d41d8cd98f00b204e9800998ecf8427e",
+            "crc32:00000003",
+            "sha1:da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        ]
+    );
+}
+
+#[test]
+fn test_game_partitions() {
+    let input = r#"
+<datafile>
+    <game name="bios" isbios="yes"/>
+    <game name="parent" romof="bios"/>
+    <game name="clone" cloneof="parent" romof="parent"/>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    fn names<'a>(games: impl Iterator<Item = &'a Game>) -> Vec<&'a str> {
+        games.map(|game| game.name.as_str()).collect()
+    }
+    assert_eq!(names(data_file.bios_games()), ["bios"]);
+    assert_eq!(names(data_file.regular_games()), ["parent", "clone"]);
+    assert_eq!(
+        names(data_file.games.iter().filter(|game| game.is_parent())),
+        ["bios", "parent"]
+    );
+    assert!(data_file.games[2].is_clone());
+}
+
+#[test]
+fn test_clone_chain() {
+    let input = r#"
+<datafile>
+    <game name="parent"/>
+    <game name="clone" cloneof="parent"/>
+    <game name="clone2" cloneof="clone"/>
+    <game name="self" cloneof="self"/>
+    <game name="orphan" cloneof="missing"/>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let names = |name: &str| {
+        data_file.clone_chain(name).map(|chain| {
+            chain
+                .iter()
+                .map(|game| game.name.as_str())
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(names("clone2"), Ok(vec!["clone2", "clone", "parent"]));
+    assert_eq!(names("parent"), Ok(vec!["parent"]));
+    assert_eq!(
+        names("self"),
+        Err(CloneChainError::Cycle("self".to_owned()))
+    );
+    assert_eq!(
+        names("orphan"),
+        Err(CloneChainError::MissingGame("missing".to_owned()))
+    );
+}
+
+#[test]
+fn test_parent_of() {
+    let input = r#"
+<datafile>
+    <game name="parent"/>
+    <game name="clone" cloneof="parent" romof="parent"/>
+    <game name="romof" romof="parent"/>
+    <game name="orphan" cloneof="missing"/>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let parent_name = |name: &str| {
+        data_file
+            .parent_of(data_file.game(name).unwrap())
+            .map(|game| game.name.as_str())
+    };
+    assert_eq!(parent_name("clone"), Some("parent"));
+    assert_eq!(parent_name("romof"), Some("parent"));
+    assert_eq!(parent_name("parent"), None);
+    assert_eq!(parent_name("orphan"), None);
+}
+
+#[test]
+fn test_primary_rom() {
+    let input = r#"
+<datafile>
+    <game name="Single">
+        <rom name="single.bin"/>
+    </game>
+    <game name="Multi">
+        <rom name="small.bin" size="16"/>
+        <rom name="unknown.bin"/>
+        <rom name="large.bin" size="1024"/>
+        <rom name="large2.bin" size="1024"/>
+    </game>
+    <game name="Empty"/>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let primary = |idx: usize| {
+        data_file.games[idx]
+            .primary_rom()
+            .map(|rom| rom.name.as_str())
+    };
+    assert_eq!(primary(0), Some("single.bin"));
+    assert_eq!(primary(1), Some("large.bin"));
+    assert_eq!(primary(2), None);
+}
+
+#[test]
+fn test_tracks() {
+    let input = r#"
+<datafile>
+    <game name="Game">
+        <rom name="Game.cue" size="300" crc="00000001"/>
+        <rom name="Game (Track 2).bin" size="200" crc="00000002" track="2"/>
+        <rom name="Game (Track 10).bin" size="100" crc="00000003" track="10"/>
+        <rom name="Game (Track 1).bin" size="400" crc="00000004" number="1"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let game = &data_file.games[0];
+    assert_eq!(
+        game.tracks()
+            .iter()
+            .map(|rom| rom.name.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "Game (Track 1).bin",
+            "Game (Track 2).bin",
+            "Game (Track 10).bin",
+            "Game.cue"
+        ]
+    );
+    let output = crate::DatWriter::to_string(&data_file).unwrap();
+    assert!(output.contains(r#"track="10""#));
+    assert!(output.contains(r#"number="1""#));
+    assert_eq!(
+        crate::DatReader::from_string(&output).read_all().unwrap(),
+        data_file
+    );
+}
+
+#[test]
+fn test_crc_collisions() {
+    let input = r#"
+<datafile>
+    <game name="A">
+        <rom name="a.bin" size="1" crc="deadbeef"/>
+        <rom name="unique.bin" size="1" crc="12345678"/>
+    </game>
+    <game name="B">
+        <rom name="b.bin" size="1" crc="deadbeef"/>
+        <rom name="nocrc.bin" size="1"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    let collisions = data_file.crc_collisions();
+    assert_eq!(collisions.len(), 1);
+    let (crc, roms) = &collisions[0];
+    assert_eq!(*crc, Crc32(0xdeadbeef));
+    let names = roms
+        .iter()
+        .map(|(game, rom)| (game.name.as_str(), rom.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("A", "a.bin"), ("B", "b.bin")]);
+}
@@ -0,0 +1,232 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+use crate::DataFile;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeStrategy {
+    pub games: GameConflict,
+    pub header: HeaderMerge,
+}
+
+/// What to do when both files contain a game with the same name
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum GameConflict {
+    Replace,
+    Skip,
+    #[default]
+    Error,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum HeaderMerge {
+    #[default]
+    KeepBase,
+    TakeOther,
+    RequireEqual,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub replaced: usize,
+    pub skipped: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeError {
+    DuplicateGame(String),
+    HeaderMismatch,
+}
+
+impl Error for MergeError {}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::MergeError::*;
+        match self {
+            DuplicateGame(name) => write!(f, "Duplicate game name \"{}\"", name),
+            HeaderMismatch => write!(f, "Headers of merged files differ"),
+        }
+    }
+}
+
+impl DataFile {
+    /// Merges the games of `other` into this file.
+    ///
+    /// Games of `other` are merged in order, so a game that appears twice in `other` conflicts
+    /// with its first copy, and `GameConflict::Error` rejects it too. Conflicts are checked
+    /// before anything is modified, so on error this file is left untouched.
+    pub fn merge(
+        &mut self,
+        other: DataFile,
+        strategy: MergeStrategy,
+    ) -> Result<MergeSummary, MergeError> {
+        if strategy.header == HeaderMerge::RequireEqual && self.header != other.header {
+            return Err(MergeError::HeaderMismatch);
+        }
+        let mut index = self
+            .games
+            .iter()
+            .enumerate()
+            .map(|(idx, game)| (game.name.clone(), idx))
+            .collect::<HashMap<_, _>>();
+        if strategy.games == GameConflict::Error {
+            let mut seen = HashSet::new();
+            if let Some(game) = other
+                .games
+                .iter()
+                .find(|game| index.contains_key(&game.name) || !seen.insert(&game.name))
+            {
+                return Err(MergeError::DuplicateGame(game.name.clone()));
+            }
+        }
+        if strategy.header == HeaderMerge::TakeOther && other.header.is_some() {
+            self.header = other.header;
+        }
+        let mut summary = MergeSummary::default();
+        for game in other.games {
+            match index.get(&game.name) {
+                Some(&idx) if strategy.games == GameConflict::Replace => {
+                    self.games[idx] = game;
+                    summary.replaced += 1;
+                }
+                Some(_) => summary.skipped += 1,
+                None => {
+                    index.insert(game.name.clone(), self.games.len());
+                    self.games.push(game);
+                    summary.added += 1;
+                }
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+fn merge_test_files() -> (DataFile, DataFile) {
+    let base = crate::DatReader::from_string(
+        r#"
+<datafile>
+    <header><name>Base</name></header>
+    <game name="A"><description>Base A</description></game>
+    <game name="B"><description>Base B</description></game>
+</datafile>"#,
+    )
+    .read_all()
+    .unwrap();
+    let update = crate::DatReader::from_string(
+        r#"
+<datafile>
+    <header><name>Update</name></header>
+    <game name="B"><description>Update B</description></game>
+    <game name="C"><description>Update C</description></game>
+</datafile>"#,
+    )
+    .read_all()
+    .unwrap();
+    (base, update)
+}
+
+#[test]
+fn test_merge_replace() {
+    let (mut base, update) = merge_test_files();
+    let strategy = MergeStrategy {
+        games: GameConflict::Replace,
+        header: HeaderMerge::TakeOther,
+    };
+    let summary = base.merge(update, strategy).unwrap();
+    assert_eq!(
+        summary,
+        MergeSummary {
+            added: 1,
+            replaced: 1,
+            skipped: 0
+        }
+    );
+    let descriptions = base
+        .games
+        .iter()
+        .map(|game| game.description.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(descriptions, vec!["Base A", "Update B", "Update C"]);
+    assert_eq!(base.header.unwrap().name, "Update");
+}
+
+#[test]
+fn test_merge_skip() {
+    let (mut base, update) = merge_test_files();
+    let strategy = MergeStrategy {
+        games: GameConflict::Skip,
+        header: HeaderMerge::KeepBase,
+    };
+    let summary = base.merge(update, strategy).unwrap();
+    assert_eq!(
+        summary,
+        MergeSummary {
+            added: 1,
+            replaced: 0,
+            skipped: 1
+        }
+    );
+    assert_eq!(base.games[1].description, "Base B");
+    assert_eq!(base.games[2].name, "C");
+    assert_eq!(base.header.unwrap().name, "Base");
+}
+
+#[test]
+fn test_merge_errors() {
+    let (mut base, update) = merge_test_files();
+    let original = base.clone();
+    assert_eq!(
+        base.merge(update.clone(), MergeStrategy::default()),
+        Err(MergeError::DuplicateGame("B".to_owned()))
+    );
+    let strategy = MergeStrategy {
+        games: GameConflict::Skip,
+        header: HeaderMerge::RequireEqual,
+    };
+    assert_eq!(
+        base.merge(update, strategy),
+        Err(MergeError::HeaderMismatch)
+    );
+    assert_eq!(base, original);
+}
+
+#[test]
+fn test_merge_duplicates_in_other() {
+    let (mut base, mut update) = merge_test_files();
+    let mut second_c = update.games[1].clone();
+    second_c.description = "Update C again".to_owned();
+    update.games.push(second_c);
+    let mut empty = DataFile::default();
+    assert_eq!(
+        empty.merge(update.clone(), MergeStrategy::default()),
+        Err(MergeError::DuplicateGame("C".to_owned()))
+    );
+    assert!(empty.games.is_empty());
+
+    let strategy = |games| MergeStrategy {
+        games,
+        header: HeaderMerge::KeepBase,
+    };
+    let mut skipped = base.clone();
+    let summary = skipped
+        .merge(update.clone(), strategy(GameConflict::Skip))
+        .unwrap();
+    assert_eq!(summary.skipped, 2);
+    assert_eq!(skipped.games.len(), 3);
+    assert_eq!(skipped.games[2].description, "Update C");
+
+    let summary = base.merge(update, strategy(GameConflict::Replace)).unwrap();
+    assert_eq!(summary.replaced, 2);
+    assert_eq!(base.games.len(), 3);
+    assert_eq!(base.games[2].description, "Update C again");
+}
@@ -0,0 +1,367 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{Crc32, DatDate, Md5, Sha1, Sha256};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataFile {
+    pub element_kind: DataFileElementKind,
+    pub id: String, // No-Intro extension
+    pub build: String,
+    pub debug: bool,
+    /// Optional per the DTD. Its position isn't checked, and repeated headers are read into
+    /// the same value.
+    pub header: Option<Header>,
+    pub games: Vec<Game>,
+    pub unknown: UnknownContent,
+}
+
+impl DataFile {
+    pub fn into_games(self) -> vec::IntoIter<Game> {
+        self.games.into_iter()
+    }
+    pub fn into_header(self) -> Option<Header> {
+        self.header
+    }
+}
+
+impl<'a> IntoIterator for &'a DataFile {
+    type Item = &'a Game;
+    type IntoIter = core::slice::Iter<'a, Game>;
+    fn into_iter(self) -> core::slice::Iter<'a, Game> {
+        self.games.iter()
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    pub id: String, // No-Intro extension
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub version: String,
+    pub date: String,
+    pub author: String,
+    pub email: String,
+    pub homepage: String,
+    pub url: String,
+    pub comment: String,
+    pub subset: String, // Redump extension
+    pub retool: String, // Retool extension
+    pub clr_mame_pro: Option<ClrMamePro>,
+    pub rom_center: Option<RomCenter>,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClrMamePro {
+    pub header: String,
+    pub force_merging: ForceMerging,
+    pub force_no_dump: ForceNoDump,
+    pub force_packing: ForcePacking,
+    pub version: String,
+    pub date: String,
+    pub comment: String,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ForceMerging {
+    None,
+    #[default]
+    Split,
+    Full,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ForceNoDump {
+    #[default]
+    Obsolete,
+    Required,
+    Ignore,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ForcePacking {
+    #[default]
+    Zip,
+    Unzip,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RomCenter {
+    pub plugin: String,
+    pub rom_mode: RomMode,
+    pub bios_mode: RomMode,
+    pub sample_mode: SampleMode,
+    pub lock_rom_mode: bool,
+    pub lock_bios_mode: bool,
+    pub lock_sample_mode: bool,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RomMode {
+    Merged,
+    #[default]
+    Split,
+    Unmerged,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SampleMode {
+    #[default]
+    Merged,
+    Unmerged,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    pub element_kind: GameElementKind,
+    pub id: String, // No-Intro extension
+    pub name: String,
+    pub description: String,
+    pub is_bios: bool,
+    pub source_file: String,
+    pub clone_of: String,
+    pub clone_of_id: String, // No-Intro extension
+    pub rom_of: String,
+    pub sample_of: String,
+    pub board: String,
+    pub rebuild_to: String,
+    pub comments: Vec<String>,
+    pub year: String,
+    pub manufacturer: String,
+    pub releases: Vec<Release>,
+    pub bios_sets: Vec<BiosSet>,
+    pub roms: Vec<Rom>,
+    pub disks: Vec<Disk>,
+    pub samples: Vec<Sample>,
+    pub archives: Vec<Archive>,
+    pub device_refs: Vec<DeviceRef>, // MAME extension
+    pub driver: Option<Driver>,      // MAME extension
+    pub unknown: UnknownContent,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DataFileElementKind {
+    #[default]
+    DataFile,
+    Mame, // MAME -listxml
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum GameElementKind {
+    #[default]
+    Game,
+    Machine, // MAME extension
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Release {
+    pub name: String,
+    pub region: String,
+    pub language: String,
+    pub date: DatDate,
+    pub default: bool,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiosSet {
+    pub name: String,
+    pub description: String,
+    pub default: bool,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rom {
+    pub name: String,
+    pub size: Option<u64>,
+    pub crc: Option<Crc32>,
+    pub sha1: Option<Sha1>,
+    pub sha256: Option<Sha256>, // No-Intro extension
+    pub md5: Option<Md5>,
+    pub merge: String,
+    pub bios: String, // MAME extension
+    /// Header skipper profile, e.g. "No-Intro_NES.xml", to apply before hashing
+    pub header_ref: String,
+    pub track: Option<u64>,  // Redump extension
+    pub number: Option<u64>, // Redump extension
+    pub status: Status,
+    pub date: DatDate,
+    pub serial: String, // No-Intro extension
+    pub region: String, // No-Intro extension
+    pub flags: String,  // No-Intro extension
+    pub mia: bool,      // No-Intro extension
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Status {
+    BadDump,
+    NoDump,
+    #[default]
+    Good,
+    Verified,
+    Other(String), // Unrecognized value, kept as-is
+}
+
+impl Status {
+    /// Ranks dump quality from worst to best, for picking the best of several dumps:
+    /// nodump (no data at all) < baddump < unrecognized values < good < verified.
+    ///
+    /// Unlike the derived `Ord`, a bad dump ranks above a missing one.
+    pub fn quality_rank(&self) -> u8 {
+        match self {
+            Status::NoDump => 0,
+            Status::BadDump => 1,
+            Status::Other(_) => 2,
+            Status::Good => 3,
+            Status::Verified => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Disk {
+    pub name: String,
+    pub sha1: Option<Sha1>,
+    pub md5: Option<Md5>,
+    pub merge: String,
+    pub status: Status,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample {
+    pub name: String,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Archive {
+    pub name: String,
+    // No-Intro parent/clone export extensions
+    pub number: String,
+    pub clone: String,
+    pub region: String,
+    pub languages: String,
+    pub version: String,
+    pub dev_status: String,
+    pub additional: String,
+    pub special: String,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceRef {
+    pub name: String,
+    pub unknown: UnknownContent,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Driver {
+    pub status: DriverStatus,
+    pub emulation: DriverStatus,
+    pub save_state: SaveState,
+    pub unknown: UnknownContent,
+}
+
+/// MAME `-listxml` driver status. This is a separate vocabulary from the rom/disk `Status`, and
+/// can be parsed from MAME strings with `str::parse`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DriverStatus {
+    #[default]
+    Good,
+    Imperfect,
+    Preliminary,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SaveState {
+    Supported,
+    #[default]
+    Unsupported,
+}
+
+/// Unrecognized attributes and child elements captured when
+/// `DatReader::set_preserve_unknown` is enabled
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownContent {
+    pub attrs: Vec<(String, String)>,
+    pub elements: Vec<UnknownElement>,
+    /// XML comments captured when `DatReader::set_preserve_comments` is enabled. They are
+    /// written back at the start of the element.
+    pub comments: Vec<String>,
+    /// `<?target content?>` processing instructions captured when
+    /// `DatReader::set_preserve_processing_instructions` is enabled. They are written back after
+    /// the comments.
+    pub processing_instructions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownElement {
+    pub tag: String,
+    pub attrs: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<UnknownElement>,
+}
+
+#[test]
+fn test_data_file_into_iter() {
+    use alloc::borrow::ToOwned;
+
+    let game = |name: &str| Game {
+        name: name.to_owned(),
+        ..Game::default()
+    };
+    let data_file = DataFile {
+        games: vec![game("B"), game("A"), game("C")],
+        ..DataFile::default()
+    };
+    let mut names = Vec::new();
+    for game in &data_file {
+        names.push(game.name.as_str());
+    }
+    assert_eq!(names, ["B", "A", "C"]);
+}
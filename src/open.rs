@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{DatReader, DatReaderError};
+
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+
+impl DatReader<Box<dyn BufRead + Send>> {
+    /// Opens a plain, gzip-compressed, or zipped DAT file. The format is detected from the first
+    /// bytes of the file, so the extension doesn't matter.
+    ///
+    /// Gzip needs the "flate2" feature and zip the "zip" feature. Without them, those formats
+    /// fail with `DatReaderError::UnsupportedFormat`.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+        let file = File::open(path).map_err(quick_xml::Error::from)?;
+        let mut reader = BufReader::new(file);
+        let buf = reader.fill_buf().map_err(quick_xml::Error::from)?;
+        if buf.starts_with(&GZIP_MAGIC) {
+            open_gzip(reader)
+        } else if buf.starts_with(&ZIP_MAGIC) {
+            open_zip(reader)
+        } else {
+            Ok(DatReader::from_reader(Box::new(reader)))
+        }
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn open_gzip(
+    reader: BufReader<File>,
+) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+    DatReader::from_gzip_reader(reader)
+}
+
+#[cfg(not(feature = "flate2"))]
+fn open_gzip(_: BufReader<File>) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+    Err(DatReaderError::UnsupportedFormat(
+        "Reading gzip-compressed files requires the \"flate2\" feature".to_owned(),
+    ))
+}
+
+#[cfg(feature = "zip")]
+fn open_zip(reader: BufReader<File>) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+    let buf = crate::archive::read_zip_entry(reader, None)?;
+    Ok(DatReader::from_reader(Box::new(std::io::Cursor::new(buf))))
+}
+
+#[cfg(not(feature = "zip"))]
+fn open_zip(_: BufReader<File>) -> Result<DatReader<Box<dyn BufRead + Send>>, DatReaderError> {
+    Err(DatReaderError::UnsupportedFormat(
+        "Reading zip archives requires the \"zip\" feature".to_owned(),
+    ))
+}
+
+#[cfg(test)]
+fn write_test_file(name: &str, contents: &[u8]) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn test_open_plain() {
+    let (_dir, path) = write_test_file("plain.dat", crate::FULL_PARSE_INPUT.as_bytes());
+    let data_file = DatReader::open(&path).unwrap().read_all().unwrap();
+    let expected = DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file, expected);
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_open_gzip() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(crate::FULL_PARSE_INPUT.as_bytes())
+        .unwrap();
+    let (_dir, path) = write_test_file("file.dat.gz", &encoder.finish().unwrap());
+    let data_file = DatReader::open(&path).unwrap().read_all().unwrap();
+    assert_eq!(data_file.games.len(), 2);
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_open_zip() {
+    use std::io::{Cursor, Write};
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer
+        .start_file("file.dat", SimpleFileOptions::default())
+        .unwrap();
+    writer
+        .write_all(crate::FULL_PARSE_INPUT.as_bytes())
+        .unwrap();
+    let zip = writer.finish().unwrap().into_inner();
+    let (_dir, path) = write_test_file("file.zip", &zip);
+    let data_file = DatReader::open(&path).unwrap().read_all().unwrap();
+    assert_eq!(data_file.games.len(), 2);
+}
+
+#[cfg(not(feature = "zip"))]
+#[test]
+fn test_open_zip_unsupported() {
+    let (_dir, path) = write_test_file("file.zip", b"PK\x03\x04");
+    assert!(matches!(
+        DatReader::open(&path),
+        Err(DatReaderError::UnsupportedFormat(_))
+    ));
+}
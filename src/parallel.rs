@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use rayon::prelude::*;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{DatReader, DatReaderError, DataFile};
+
+/// Parses every `.dat` and `.xml` file in a directory in parallel.
+///
+/// Results are sorted by path, and a file that fails to parse doesn't affect the others.
+pub fn read_dir_parallel<P: AsRef<Path>>(
+    dir: P,
+) -> io::Result<Vec<(PathBuf, Result<DataFile, DatReaderError>)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_dat = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dat") || ext.eq_ignore_ascii_case("xml"));
+        if is_dat && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths
+        .into_par_iter()
+        .map(|path| {
+            let result = DatReader::from_file(&path).and_then(|reader| reader.read_all());
+            (path, result)
+        })
+        .collect())
+}
+
+#[test]
+fn test_read_dir_parallel() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.dat"), crate::FULL_PARSE_INPUT).unwrap();
+    fs::write(dir.path().join("b.xml"), "<datafile><game name=\"X\">").unwrap();
+    fs::write(dir.path().join("readme.txt"), "not a dat").unwrap();
+    let results = read_dir_parallel(dir.path()).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, dir.path().join("a.dat"));
+    assert_eq!(results[0].1.as_ref().unwrap().games.len(), 2);
+    assert_eq!(results[1].0, dir.path().join("b.xml"));
+    assert!(matches!(
+        results[1].1,
+        Err(DatReaderError::UnexpectedEof(_))
+    ));
+}
@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Crc32, DataFile, Sha1, Status};
+
+/// One rom flattened together with its game, e.g. for writing a CSV row
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RomRecord<'a> {
+    pub game: &'a str,
+    pub name: &'a str,
+    pub size: Option<u64>,
+    pub crc: Option<Crc32>,
+    pub sha1: Option<Sha1>,
+    pub status: &'a Status,
+}
+
+impl DataFile {
+    pub fn rom_records(&self) -> impl Iterator<Item = RomRecord<'_>> {
+        self.games.iter().flat_map(|game| {
+            game.roms.iter().map(move |rom| RomRecord {
+                game: &game.name,
+                name: &rom.name,
+                size: rom.size,
+                crc: rom.crc,
+                sha1: rom.sha1,
+                status: &rom.status,
+            })
+        })
+    }
+}
+
+#[test]
+fn test_rom_records() {
+    let data_file = crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file.rom_records().count(), 2);
+    assert_eq!(
+        data_file.rom_records().next(),
+        Some(RomRecord {
+            game: "Name",
+            name: "Name1",
+            size: Some(1024),
+            crc: Some(Crc32(0x0123abcd)),
+            sha1: Sha1::parse("da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            status: &Status::BadDump,
+        })
+    );
+}
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Release;
+
+// Canonical names follow No-Intro. Aliases are matched case-insensitively and cover the
+// No-Intro/TOSEC style three-letter codes, ISO 3166 codes, and common spellings.
+const REGIONS: &[(&str, &[&str])] = &[
+    ("USA", &["usa", "us", "u", "united states", "america"]),
+    ("Japan", &["japan", "jpn", "jp", "j"]),
+    ("Europe", &["europe", "eur", "eu", "e"]),
+    ("World", &["world", "wor", "w"]),
+    ("Asia", &["asia", "asi"]),
+    ("Australia", &["australia", "aus", "au"]),
+    ("Brazil", &["brazil", "bra", "br"]),
+    ("Canada", &["canada", "can", "ca"]),
+    ("China", &["china", "chn", "cn"]),
+    ("France", &["france", "fra", "fr"]),
+    ("Germany", &["germany", "ger", "deu", "de"]),
+    ("Italy", &["italy", "ita", "it"]),
+    ("Korea", &["korea", "kor", "kr", "south korea"]),
+    ("Netherlands", &["netherlands", "hol", "nld", "nl"]),
+    ("Spain", &["spain", "spa", "esp", "es"]),
+    ("Sweden", &["sweden", "swe", "se"]),
+    ("Taiwan", &["taiwan", "twn", "tw"]),
+    ("United Kingdom", &["united kingdom", "uk", "gbr", "gb"]),
+];
+
+impl Release {
+    /// Maps common spellings of the region (e.g. "US", "JPN", "eur") to a canonical No-Intro
+    /// style name. Returns `None` for regions not in the internal table.
+    pub fn normalized_region(&self) -> Option<&'static str> {
+        let region = self.region.trim();
+        REGIONS
+            .iter()
+            .find(|(_, aliases)| {
+                aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(region))
+            })
+            .map(|(name, _)| *name)
+    }
+}
+
+#[test]
+fn test_normalized_region() {
+    let region = |region: &str| {
+        Release {
+            region: region.to_owned(),
+            ..Release::default()
+        }
+        .normalized_region()
+    };
+    assert_eq!(region("USA"), Some("USA"));
+    assert_eq!(region("US"), Some("USA"));
+    assert_eq!(region("Japan"), Some("Japan"));
+    assert_eq!(region("JP"), Some("Japan"));
+    assert_eq!(region("jpn"), Some("Japan"));
+    assert_eq!(region("EUR"), Some("Europe"));
+    assert_eq!(region("UK"), Some("United Kingdom"));
+    assert_eq!(region("Atlantis"), None);
+    assert_eq!(region(""), None);
+}
@@ -0,0 +1,180 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::cmp::Ordering;
+
+use crate::{DataFile, Game, Rom};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RomOrder {
+    #[default]
+    Name,
+    Crc, // Roms without a CRC go last
+    ClrMamePro,
+}
+
+impl DataFile {
+    /// Sorts games by name, and roms, disks and releases within each game by name.
+    pub fn sort(&mut self) {
+        self.sort_by(RomOrder::Name)
+    }
+    /// Sorts games by a key, keeping the original order of games with equal keys.
+    pub fn sort_games_by_key<K: Ord, F: FnMut(&Game) -> K>(&mut self, f: F) {
+        self.games.sort_by_key(f)
+    }
+    pub fn sort_by(&mut self, rom_order: RomOrder) {
+        self.games.sort_by(|a, b| a.name.cmp(&b.name));
+        for game in &mut self.games {
+            match rom_order {
+                RomOrder::Name => game.roms.sort_by(|a, b| a.name.cmp(&b.name)),
+                RomOrder::Crc => game.roms.sort_by(|a, b| {
+                    (a.crc.is_none(), a.crc, &a.name).cmp(&(b.crc.is_none(), b.crc, &b.name))
+                }),
+                RomOrder::ClrMamePro => game.roms.sort_by(Rom::cmp_clrmamepro),
+            }
+            game.disks.sort_by(|a, b| a.name.cmp(&b.name));
+            game.releases.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+}
+
+impl Rom {
+    /// Orders roms by name like ClrMamePro: case-insensitively, with digit runs compared as
+    /// numbers so "rom2" sorts before "rom10"
+    pub fn cmp_clrmamepro(&self, other: &Rom) -> Ordering {
+        natural_cmp(&self.name, &other.name).then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, rest_a) = split_digits(a);
+                let (y, rest_b) = split_digits(b);
+                let (x_value, y_value) = (trim_zeros(x), trim_zeros(y));
+                let ordering = x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = rest_a;
+                b = rest_b;
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+fn split_digits(value: &[u8]) -> (&[u8], &[u8]) {
+    let end = value
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(value.len());
+    value.split_at(end)
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|&b| b != b'0')
+        .unwrap_or(digits.len());
+    &digits[start..]
+}
+
+#[test]
+fn test_sort() {
+    let shuffled = r#"
+<datafile>
+    <game name="b">
+        <rom name="b2" crc="00000001"/>
+        <rom name="b3"/>
+        <rom name="b1" crc="00000002"/>
+        <disk name="d2"/>
+        <disk name="d1"/>
+    </game>
+    <game name="a">
+        <release name="r2" region="EUR"/>
+        <release name="r1" region="USA"/>
+    </game>
+</datafile>"#;
+    let canonical = r#"
+<datafile>
+    <game name="a">
+        <release name="r1" region="USA"/>
+        <release name="r2" region="EUR"/>
+    </game>
+    <game name="b">
+        <rom name="b1" crc="00000002"/>
+        <rom name="b2" crc="00000001"/>
+        <rom name="b3"/>
+        <disk name="d1"/>
+        <disk name="d2"/>
+    </game>
+</datafile>"#;
+    let read = |input: &str| crate::DatReader::from_string(input).read_all().unwrap();
+    let mut data_file = read(shuffled);
+    data_file.sort();
+    let expected = read(canonical);
+    assert_eq!(data_file, expected);
+    assert_eq!(
+        crate::DatWriter::to_string(&data_file).unwrap(),
+        crate::DatWriter::to_string(&expected).unwrap()
+    );
+
+    data_file.sort_by(RomOrder::Crc);
+    let names = data_file.games[1]
+        .roms
+        .iter()
+        .map(|rom| rom.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["b2", "b1", "b3"]);
+}
+
+#[test]
+fn test_cmp_clrmamepro() {
+    let mut roms = ["rom10", "ROM2", "rom1b", "rom1", "Rom02", "a"]
+        .iter()
+        .map(|name| Rom {
+            name: name.to_string(),
+            ..Rom::default()
+        })
+        .collect::<Vec<_>>();
+    roms.sort_by(Rom::cmp_clrmamepro);
+    let names = roms.iter().map(|rom| rom.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["a", "rom1", "rom1b", "ROM2", "Rom02", "rom10"]);
+}
+
+#[test]
+fn test_sort_games_by_key() {
+    let input = r#"
+<datafile>
+    <game name="c"><manufacturer>Sega</manufacturer></game>
+    <game name="a"><manufacturer>Nintendo</manufacturer></game>
+    <game name="b"><manufacturer>Capcom</manufacturer></game>
+    <game name="d"><manufacturer>Nintendo</manufacturer></game>
+</datafile>"#;
+    let mut data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    data_file.sort_games_by_key(|game| game.manufacturer.clone());
+    let names = data_file
+        .games
+        .iter()
+        .map(|game| game.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["b", "a", "d", "c"]);
+}
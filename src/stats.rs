@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeMap;
+
+use crate::{DataFile, Rom, Status};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatStats {
+    pub games: usize,
+    pub bios_games: usize,
+    pub roms: usize,
+    pub disks: usize,
+    /// Rom and disk counts by status
+    pub by_status: BTreeMap<Status, usize>,
+    /// Sum of all known rom sizes
+    pub total_size: u128,
+}
+
+impl DataFile {
+    pub fn stats(&self) -> DatStats {
+        let mut stats = DatStats {
+            games: self.games.len(),
+            ..DatStats::default()
+        };
+        for game in &self.games {
+            if game.is_bios {
+                stats.bios_games += 1;
+            }
+            stats.roms += game.roms.len();
+            stats.disks += game.disks.len();
+            for rom in &game.roms {
+                *stats.by_status.entry(rom.status.clone()).or_insert(0) += 1;
+                stats.total_size += u128::from(rom.size.unwrap_or(0));
+            }
+            for disk in &game.disks {
+                *stats.by_status.entry(disk.status.clone()).or_insert(0) += 1;
+            }
+        }
+        stats
+    }
+    /// Sum of all known rom sizes
+    pub fn total_size(&self) -> u128 {
+        self.games
+            .iter()
+            .flat_map(|game| &game.roms)
+            .filter_map(|rom| rom.size)
+            .map(u128::from)
+            .sum()
+    }
+}
+
+impl Rom {
+    /// Formats the size with IEC units, e.g. "1.5 MiB". Sizes below 1 KiB are shown in bytes.
+    pub fn size_human(&self) -> Option<String> {
+        self.size.map(format_size)
+    }
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[test]
+fn test_stats() {
+    let data_file = crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    let stats = data_file.stats();
+    assert_eq!(
+        stats,
+        DatStats {
+            games: 2,
+            bios_games: 1,
+            roms: 2,
+            disks: 2,
+            by_status: vec![(Status::BadDump, 2), (Status::Verified, 2)]
+                .into_iter()
+                .collect(),
+            total_size: 1024 + 0x800,
+        }
+    );
+    assert_eq!(DataFile::default().stats(), DatStats::default());
+}
+
+#[test]
+fn test_size_human() {
+    let rom = |size| Rom {
+        size,
+        ..Rom::default()
+    };
+    assert_eq!(rom(None).size_human(), None);
+    assert_eq!(rom(Some(512)).size_human().unwrap(), "512 B");
+    assert_eq!(rom(Some(1024)).size_human().unwrap(), "1.0 KiB");
+    assert_eq!(rom(Some(1536)).size_human().unwrap(), "1.5 KiB");
+    assert_eq!(rom(Some(1048576)).size_human().unwrap(), "1.0 MiB");
+    assert_eq!(rom(Some(u64::MAX)).size_human().unwrap(), "16.0 EiB");
+}
+
+#[test]
+fn test_total_size() {
+    let data_file = crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_all()
+        .unwrap();
+    assert_eq!(data_file.total_size(), 1024 + 0x800);
+    let input = r#"
+<datafile>
+    <game name="A">
+        <rom name="a1" size="0xffffffffffffffff"/>
+        <rom name="a2" size="2"/>
+        <rom name="a3" status="nodump"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(data_file.total_size(), u64::MAX as u128 + 2);
+    assert_eq!(data_file.stats().total_size, u64::MAX as u128 + 2);
+}
@@ -0,0 +1,349 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{DataFile, ForceMerging, RomMode, Status};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidationError {
+    DanglingCloneOf {
+        game: String,
+        clone_of: String,
+    },
+    DanglingRomOf {
+        game: String,
+        rom_of: String,
+    },
+    DanglingSampleOf {
+        game: String,
+        sample_of: String,
+    },
+    DanglingMerge {
+        game: String,
+        rom: String,
+        merge: String,
+    },
+    DuplicateGameName {
+        game: String,
+    },
+    DuplicateRomName {
+        game: String,
+        rom: String,
+    },
+    MissingHashes {
+        game: String,
+        rom: String,
+    },
+    UnexpectedHashes {
+        game: String,
+        rom: String,
+    },
+    /// The header has both `<clrmamepro>` and `<romcenter>` and they ask for different merge
+    /// modes. None, split, and full merging correspond to the unmerged, split, and merged rom
+    /// modes.
+    ConflictingMergeModes {
+        force_merging: ForceMerging,
+        rom_mode: RomMode,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use crate::ValidationError::*;
+        match self {
+            DanglingCloneOf { game, clone_of } => write!(
+                f,
+                "Game \"{}\" is a clone of missing game \"{}\"",
+                game, clone_of
+            ),
+            DanglingRomOf { game, rom_of } => write!(
+                f,
+                "Game \"{}\" uses roms of missing game \"{}\"",
+                game, rom_of
+            ),
+            DanglingSampleOf { game, sample_of } => write!(
+                f,
+                "Game \"{}\" uses samples of missing game \"{}\"",
+                game, sample_of
+            ),
+            DanglingMerge { game, rom, merge } => write!(
+                f,
+                "Rom \"{}\" in game \"{}\" merges missing parent rom \"{}\"",
+                rom, game, merge
+            ),
+            DuplicateGameName { game } => write!(f, "Duplicate game name \"{}\"", game),
+            DuplicateRomName { game, rom } => {
+                write!(f, "Duplicate rom name \"{}\" in game \"{}\"", rom, game)
+            }
+            MissingHashes { game, rom } => write!(
+                f,
+                "Rom \"{}\" in game \"{}\" has neither a CRC nor a SHA1",
+                rom, game
+            ),
+            UnexpectedHashes { game, rom } => write!(
+                f,
+                "Rom \"{}\" in game \"{}\" is marked nodump but has hashes",
+                rom, game
+            ),
+            ConflictingMergeModes {
+                force_merging,
+                rom_mode,
+            } => write!(
+                f,
+                "Header forces {} merging but the RomCenter rom mode is {}",
+                force_merging, rom_mode
+            ),
+        }
+    }
+}
+
+impl DataFile {
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        if let Some(header) = &self.header {
+            if let (Some(clr_mame_pro), Some(rom_center)) =
+                (&header.clr_mame_pro, &header.rom_center)
+            {
+                let rom_mode = match clr_mame_pro.force_merging {
+                    ForceMerging::None => RomMode::Unmerged,
+                    ForceMerging::Split => RomMode::Split,
+                    ForceMerging::Full => RomMode::Merged,
+                };
+                if rom_mode != rom_center.rom_mode {
+                    errors.push(ValidationError::ConflictingMergeModes {
+                        force_merging: clr_mame_pro.force_merging,
+                        rom_mode: rom_center.rom_mode,
+                    });
+                }
+            }
+        }
+        let mut names = HashSet::new();
+        for game in &self.games {
+            if !names.insert(game.name.as_str()) {
+                errors.push(ValidationError::DuplicateGameName {
+                    game: game.name.clone(),
+                });
+            }
+            let mut rom_names = HashSet::new();
+            for rom in &game.roms {
+                if !rom_names.insert(rom.name.as_str()) {
+                    errors.push(ValidationError::DuplicateRomName {
+                        game: game.name.clone(),
+                        rom: rom.name.clone(),
+                    });
+                }
+                let has_hashes = rom.crc.is_some() || rom.sha1.is_some();
+                match rom.status {
+                    Status::Good | Status::Verified if !has_hashes => {
+                        errors.push(ValidationError::MissingHashes {
+                            game: game.name.clone(),
+                            rom: rom.name.clone(),
+                        })
+                    }
+                    Status::NoDump if has_hashes || rom.md5.is_some() || rom.sha256.is_some() => {
+                        errors.push(ValidationError::UnexpectedHashes {
+                            game: game.name.clone(),
+                            rom: rom.name.clone(),
+                        })
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let is_dangling = |name: &str| !name.is_empty() && !names.contains(name);
+        let mut games_by_name = HashMap::new();
+        for game in &self.games {
+            games_by_name.entry(game.name.as_str()).or_insert(game);
+        }
+        for game in &self.games {
+            if is_dangling(&game.clone_of) {
+                errors.push(ValidationError::DanglingCloneOf {
+                    game: game.name.clone(),
+                    clone_of: game.clone_of.clone(),
+                });
+            }
+            if is_dangling(&game.rom_of) {
+                errors.push(ValidationError::DanglingRomOf {
+                    game: game.name.clone(),
+                    rom_of: game.rom_of.clone(),
+                });
+            }
+            if is_dangling(&game.sample_of) {
+                errors.push(ValidationError::DanglingSampleOf {
+                    game: game.name.clone(),
+                    sample_of: game.sample_of.clone(),
+                });
+            }
+            // Merges refer to the romof parent, or the cloneof parent if romof is not set
+            let parent = if game.rom_of.is_empty() {
+                &game.clone_of
+            } else {
+                &game.rom_of
+            };
+            if let Some(parent) = games_by_name.get(parent.as_str()) {
+                for rom in &game.roms {
+                    if !rom.merge.is_empty() && !parent.roms.iter().any(|r| r.name == rom.merge) {
+                        errors.push(ValidationError::DanglingMerge {
+                            game: game.name.clone(),
+                            rom: rom.name.clone(),
+                            merge: rom.merge.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[test]
+fn test_validate_references() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <game name="parent">
+        <description>Parent</description>
+    </game>
+    <game name="clone" cloneof="parent" romof="parent">
+        <description>Clone</description>
+    </game>
+    <game name="orphan" cloneof="missing" sampleof="samples">
+        <description>Orphan</description>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(
+        data_file.validate(),
+        vec![
+            ValidationError::DanglingCloneOf {
+                game: "orphan".to_owned(),
+                clone_of: "missing".to_owned(),
+            },
+            ValidationError::DanglingSampleOf {
+                game: "orphan".to_owned(),
+                sample_of: "samples".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_validate_duplicates() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <game name="Name">
+        <rom name="a" crc="00000000"/>
+        <rom name="A" crc="00000000"/>
+        <rom name="a" crc="00000000"/>
+    </game>
+    <game name="name"/>
+    <game name="Name"/>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(
+        data_file.validate(),
+        vec![
+            ValidationError::DuplicateRomName {
+                game: "Name".to_owned(),
+                rom: "a".to_owned(),
+            },
+            ValidationError::DuplicateGameName {
+                game: "Name".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_validate_merges() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <game name="parent">
+        <rom name="parent.bin" size="1" crc="00000000"/>
+    </game>
+    <game name="clone" cloneof="parent">
+        <rom name="clone.bin" merge="parent.bin" size="1" crc="00000000"/>
+        <rom name="other.bin" merge="missing.bin" size="1" crc="00000000"/>
+    </game>
+    <game name="standalone">
+        <rom name="a.bin" merge="whatever.bin" size="1" crc="00000000"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(
+        data_file.validate(),
+        vec![ValidationError::DanglingMerge {
+            game: "clone".to_owned(),
+            rom: "other.bin".to_owned(),
+            merge: "missing.bin".to_owned(),
+        }]
+    );
+}
+
+#[test]
+fn test_validate_hashes() {
+    let input = r#"
+<?xml version="1.0"?>
+<datafile>
+    <game name="Name">
+        <rom name="good" size="1" crc="00000000"/>
+        <rom name="good-missing" size="1"/>
+        <rom name="verified-sha1" size="1" sha1="da39a3ee5e6b4b0d3255bfef95601890afd80709" status="verified"/>
+        <rom name="nodump" size="1" status="nodump"/>
+        <rom name="nodump-hashed" size="1" crc="00000000" status="nodump"/>
+        <rom name="baddump" size="1" status="baddump"/>
+    </game>
+</datafile>"#;
+    let data_file = crate::DatReader::from_string(input).read_all().unwrap();
+    assert_eq!(
+        data_file.validate(),
+        vec![
+            ValidationError::MissingHashes {
+                game: "Name".to_owned(),
+                rom: "good-missing".to_owned(),
+            },
+            ValidationError::UnexpectedHashes {
+                game: "Name".to_owned(),
+                rom: "nodump-hashed".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_validate_header() {
+    let read = |clrmamepro: &str, romcenter: &str| {
+        let input = format!(
+            "<datafile><header>{}{}</header></datafile>",
+            clrmamepro, romcenter
+        );
+        crate::DatReader::from_string(&input)
+            .read_all()
+            .unwrap()
+            .validate()
+    };
+    assert_eq!(
+        read(
+            r#"<clrmamepro forcemerging="full"/>"#,
+            r#"<romcenter rommode="unmerged"/>"#
+        ),
+        vec![ValidationError::ConflictingMergeModes {
+            force_merging: ForceMerging::Full,
+            rom_mode: RomMode::Unmerged,
+        }]
+    );
+    assert!(read(
+        r#"<clrmamepro forcemerging="full"/>"#,
+        r#"<romcenter rommode="merged"/>"#
+    )
+    .is_empty());
+    assert!(read(r#"<clrmamepro forcemerging="full"/>"#, "").is_empty());
+    assert!(read(r#"<clrmamepro/>"#, r#"<romcenter/>"#).is_empty());
+}
@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use sha1::Digest;
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::{Crc32, Md5, Rom, Sha1, Sha256};
+
+/// Result of comparing a file against a rom. Each field is `None` if the rom doesn't declare
+/// that value, and otherwise tells whether the file matched it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyResult {
+    pub size: Option<bool>,
+    pub crc: Option<bool>,
+    pub sha1: Option<bool>,
+    pub sha256: Option<bool>,
+    pub md5: Option<bool>,
+}
+
+impl VerifyResult {
+    /// True if nothing declared by the rom mismatched
+    pub fn is_match(&self) -> bool {
+        [self.size, self.crc, self.sha1, self.sha256, self.md5]
+            .iter()
+            .all(|check| *check != Some(false))
+    }
+}
+
+impl Rom {
+    /// Streams the file once, computing only the hashes this rom declares
+    pub fn verify_file<P: AsRef<Path>>(&self, path: P) -> Result<VerifyResult, io::Error> {
+        self.verify_reader(File::open(path)?)
+    }
+    pub fn verify_reader<R: Read>(&self, mut reader: R) -> Result<VerifyResult, io::Error> {
+        let mut crc = self.crc.map(|_| crc32fast::Hasher::new());
+        let mut sha1 = self.sha1.map(|_| sha1::Sha1::new());
+        let mut sha256 = self.sha256.map(|_| sha2::Sha256::new());
+        let mut md5 = self.md5.map(|_| md5::Md5::new());
+        let mut size = 0;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let len = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let data = &buf[..len];
+            size += len as u64;
+            if let Some(crc) = &mut crc {
+                crc.update(data);
+            }
+            if let Some(sha1) = &mut sha1 {
+                sha1.update(data);
+            }
+            if let Some(sha256) = &mut sha256 {
+                sha256.update(data);
+            }
+            if let Some(md5) = &mut md5 {
+                md5.update(data);
+            }
+        }
+        Ok(VerifyResult {
+            size: self.size.map(|expected| expected == size),
+            crc: crc.map(|crc| Some(Crc32(crc.finalize())) == self.crc),
+            sha1: sha1.map(|sha1| Some(Sha1(sha1.finalize().into())) == self.sha1),
+            sha256: sha256.map(|sha256| Some(Sha256(sha256.finalize().into())) == self.sha256),
+            md5: md5.map(|md5| Some(Md5(md5.finalize().into())) == self.md5),
+        })
+    }
+}
+
+#[test]
+fn test_verify_file() {
+    use std::io::Write;
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"123456789").unwrap();
+    let rom = Rom {
+        size: Some(9),
+        crc: Some(Crc32(0xcbf43926)),
+        sha1: Sha1::parse("f7c3bc1d808e04732adf679965ccc34ca7ae3441"),
+        ..Rom::default()
+    };
+    assert_eq!(
+        rom.verify_file(file.path()).unwrap(),
+        VerifyResult {
+            size: Some(true),
+            crc: Some(true),
+            sha1: Some(true),
+            ..VerifyResult::default()
+        }
+    );
+    let rom = Rom {
+        size: Some(10),
+        crc: Some(Crc32(0xcbf43927)),
+        md5: Md5::parse("25f9e794323b453885f5181f1b624d0b"),
+        ..Rom::default()
+    };
+    let result = rom.verify_file(file.path()).unwrap();
+    assert_eq!(
+        result,
+        VerifyResult {
+            size: Some(false),
+            crc: Some(false),
+            md5: Some(true),
+            ..VerifyResult::default()
+        }
+    );
+    assert!(!result.is_match());
+}
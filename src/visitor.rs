@@ -0,0 +1,107 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io::BufRead;
+
+use crate::{DatReader, DatReaderError, Game, Header};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VisitControl {
+    Continue,
+    Stop,
+}
+
+pub trait DatVisitor {
+    fn on_header(&mut self, _: &Header) -> VisitControl {
+        VisitControl::Continue
+    }
+    fn on_game(&mut self, _: &Game) -> VisitControl {
+        VisitControl::Continue
+    }
+}
+
+#[derive(Default)]
+struct HeaderVisitor {
+    header: Option<Header>,
+}
+
+impl DatVisitor for HeaderVisitor {
+    fn on_header(&mut self, header: &Header) -> VisitControl {
+        self.header = Some(header.clone());
+        VisitControl::Stop
+    }
+    fn on_game(&mut self, _: &Game) -> VisitControl {
+        VisitControl::Stop
+    }
+}
+
+impl<B: BufRead> DatReader<B> {
+    /// Reads the header and stops. Games following it are not parsed.
+    pub fn read_header(self) -> Result<Option<Header>, DatReaderError> {
+        let mut visitor = HeaderVisitor::default();
+        self.read_with_visitor(&mut visitor)?;
+        Ok(visitor.header)
+    }
+}
+
+#[test]
+fn test_visitor() {
+    #[derive(Default)]
+    struct Counter {
+        header: String,
+        games: Vec<String>,
+        limit: usize,
+    }
+    impl DatVisitor for Counter {
+        fn on_header(&mut self, header: &Header) -> VisitControl {
+            self.header = header.name.clone();
+            VisitControl::Continue
+        }
+        fn on_game(&mut self, game: &Game) -> VisitControl {
+            self.games.push(game.name.clone());
+            if self.games.len() == self.limit {
+                VisitControl::Stop
+            } else {
+                VisitControl::Continue
+            }
+        }
+    }
+
+    let mut counter = Counter {
+        limit: 1,
+        ..Counter::default()
+    };
+    crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_with_visitor(&mut counter)
+        .unwrap();
+    assert_eq!(counter.header, "Name");
+    assert_eq!(counter.games, vec!["Name"]);
+
+    let mut counter = Counter::default();
+    crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_with_visitor(&mut counter)
+        .unwrap();
+    assert_eq!(counter.games, vec!["Name", "Name2"]);
+}
+
+#[test]
+fn test_read_header() {
+    let header = crate::DatReader::from_string(crate::FULL_PARSE_INPUT)
+        .read_header()
+        .unwrap()
+        .unwrap();
+    assert_eq!(header.name, "Name");
+    assert_eq!(header.version, "Version");
+
+    // Broken games after the header are never reached
+    let input = r#"<datafile><header><name>Name</name></header><game name="X"><bogus/>"#;
+    let header = crate::DatReader::from_string(input).read_header().unwrap();
+    assert_eq!(header.map(|header| header.name), Some("Name".to_owned()));
+
+    let input = r#"<datafile><game name="X"/><game name="Y"/></datafile>"#;
+    assert_eq!(
+        crate::DatReader::from_string(input).read_header().unwrap(),
+        None
+    );
+}
@@ -2,12 +2,100 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::{ForceMerging, ForceNoDump, ForcePacking, RomMode, SampleMode, Status};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::{String, ToString},
+};
+use core::{fmt, str::FromStr};
 
+use crate::{
+    Crc32, DatDate, DriverStatus, ForceMerging, ForceNoDump, ForcePacking, Md5, RomMode,
+    SampleMode, SaveState, Sha1, Sha256, Status,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseEnumError(String);
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEnumError {}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value \"{}\"", self.0)
+    }
+}
+
+// Display and FromStr share the XML vocabulary so they can't drift apart
+macro_rules! impl_enum_str {
+    ($($ty:ident),*) => {
+        $(
+            impl fmt::Display for $ty {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str(&self.to_xml_str().unwrap_or_default())
+                }
+            }
+
+            impl FromStr for $ty {
+                type Err = ParseEnumError;
+                fn from_str(value: &str) -> Result<$ty, ParseEnumError> {
+                    let mut result = $ty::default();
+                    if result.set_from_str(value) {
+                        Ok(result)
+                    } else {
+                        Err(ParseEnumError(value.to_owned()))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_enum_str!(
+    DriverStatus,
+    ForceMerging,
+    ForceNoDump,
+    ForcePacking,
+    RomMode,
+    SampleMode,
+    SaveState,
+    Status
+);
+
+// Most values are already lowercase, so avoid allocating for them
+fn lowercase(value: &str) -> Cow<'_, str> {
+    if value.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(value.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+// Only the reader and writer need the full trait
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 pub trait XmlAttr {
     fn set_from_str(&mut self, _: &str) -> bool {
         false
     }
+    fn set_from_str_lenient(&mut self, value: &str) -> bool {
+        self.set_from_str(value)
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+    fn is_false(&self) -> bool {
+        false
+    }
+    // True for a string-like value that has no XML form only because it's empty
+    fn is_empty_str(&self) -> bool {
+        false
+    }
+    // Lets an optional attribute treat an empty value as missing instead of invalid
+    fn empty_is_absent() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
 }
 
 impl XmlAttr for String {
@@ -16,6 +104,189 @@ impl XmlAttr for String {
         self.push_str(value);
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Cow::Borrowed(self))
+        }
+    }
+    fn is_empty_str(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl XmlAttr for u64 {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        // Both parsers would also accept a leading "+"
+        let result = match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => u64::from_str_radix(hex, 16),
+            None if value.bytes().all(|b| b.is_ascii_digit()) => value.parse(),
+            _ => return false,
+        };
+        match result {
+            Ok(value) => *self = value,
+            Err(_) => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.to_string()))
+    }
+}
+
+impl XmlAttr for DriverStatus {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match lowercase(value).as_ref() {
+            "good" => *self = DriverStatus::Good,
+            "imperfect" => *self = DriverStatus::Imperfect,
+            "preliminary" => *self = DriverStatus::Preliminary,
+            _ => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            DriverStatus::Good => "good",
+            DriverStatus::Imperfect => "imperfect",
+            DriverStatus::Preliminary => "preliminary",
+        }))
+    }
+}
+
+impl XmlAttr for SaveState {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match lowercase(value).as_ref() {
+            "supported" => *self = SaveState::Supported,
+            "unsupported" => *self = SaveState::Unsupported,
+            _ => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            SaveState::Supported => "supported",
+            SaveState::Unsupported => "unsupported",
+        }))
+    }
+}
+
+impl XmlAttr for Crc32 {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match Crc32::parse(value) {
+            Some(crc) => *self = crc,
+            None => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.to_string()))
+    }
+    // Some DATs write e.g. md5="" for an unknown hash
+    fn empty_is_absent() -> bool {
+        true
+    }
+}
+
+impl XmlAttr for Sha1 {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match Sha1::parse(value) {
+            Some(sha1) => *self = sha1,
+            None => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.to_string()))
+    }
+    fn empty_is_absent() -> bool {
+        true
+    }
+}
+
+impl XmlAttr for Sha256 {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match Sha256::parse(value) {
+            Some(sha256) => *self = sha256,
+            None => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.to_string()))
+    }
+    fn empty_is_absent() -> bool {
+        true
+    }
+}
+
+impl XmlAttr for Md5 {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        match Md5::parse(value) {
+            Some(md5) => *self = md5,
+            None => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.to_string()))
+    }
+    fn empty_is_absent() -> bool {
+        true
+    }
+}
+
+impl XmlAttr for DatDate {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        // The raw value is kept even if it can't be parsed
+        *self = DatDate::new(value);
+        self.parsed.is_some()
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Cow::Borrowed(&self.raw))
+        }
+    }
+    fn is_empty_str(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T: XmlAttr + Default> XmlAttr for Option<T> {
+    fn set_from_str(&mut self, value: &str) -> bool {
+        if value.is_empty() && T::empty_is_absent() {
+            *self = None;
+            return true;
+        }
+        let mut inner = T::default();
+        if inner.set_from_str(value) {
+            *self = Some(inner);
+            true
+        } else {
+            false
+        }
+    }
+    fn set_from_str_lenient(&mut self, value: &str) -> bool {
+        if value.is_empty() && T::empty_is_absent() {
+            *self = None;
+            return true;
+        }
+        let mut inner = T::default();
+        if inner.set_from_str_lenient(value) {
+            *self = Some(inner);
+            true
+        } else {
+            false
+        }
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        self.as_ref().and_then(|inner| inner.to_xml_str())
+    }
 }
 
 impl XmlAttr for bool {
@@ -27,11 +298,25 @@ impl XmlAttr for bool {
         }
         true
     }
+    fn set_from_str_lenient(&mut self, value: &str) -> bool {
+        match value {
+            "yes" | "1" | "true" => *self = true,
+            "no" | "" | "0" | "false" => *self = false,
+            _ => return false,
+        }
+        true
+    }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(if *self { "yes" } else { "no" }))
+    }
+    fn is_false(&self) -> bool {
+        !*self
+    }
 }
 
 impl XmlAttr for ForceMerging {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "none" => *self = ForceMerging::None,
             "split" => *self = ForceMerging::Split,
             "full" => *self = ForceMerging::Full,
@@ -39,11 +324,18 @@ impl XmlAttr for ForceMerging {
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            ForceMerging::None => "none",
+            ForceMerging::Split => "split",
+            ForceMerging::Full => "full",
+        }))
+    }
 }
 
 impl XmlAttr for ForceNoDump {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "obsolete" => *self = ForceNoDump::Obsolete,
             "required" => *self = ForceNoDump::Required,
             "ignore" => *self = ForceNoDump::Ignore,
@@ -51,22 +343,35 @@ impl XmlAttr for ForceNoDump {
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            ForceNoDump::Obsolete => "obsolete",
+            ForceNoDump::Required => "required",
+            ForceNoDump::Ignore => "ignore",
+        }))
+    }
 }
 
 impl XmlAttr for ForcePacking {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "zip" => *self = ForcePacking::Zip,
             "unzip" => *self = ForcePacking::Unzip,
             _ => return false,
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            ForcePacking::Zip => "zip",
+            ForcePacking::Unzip => "unzip",
+        }))
+    }
 }
 
 impl XmlAttr for RomMode {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "merged" => *self = RomMode::Merged,
             "split" => *self = RomMode::Split,
             "unmerged" => *self = RomMode::Unmerged,
@@ -74,28 +379,90 @@ impl XmlAttr for RomMode {
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            RomMode::Merged => "merged",
+            RomMode::Split => "split",
+            RomMode::Unmerged => "unmerged",
+        }))
+    }
 }
 
 impl XmlAttr for SampleMode {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "merged" => *self = SampleMode::Merged,
             "unmerged" => *self = SampleMode::Unmerged,
             _ => return false,
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            SampleMode::Merged => "merged",
+            SampleMode::Unmerged => "unmerged",
+        }))
+    }
 }
 
 impl XmlAttr for Status {
     fn set_from_str(&mut self, value: &str) -> bool {
-        match value {
+        match lowercase(value).as_ref() {
             "baddump" => *self = Status::BadDump,
             "nodump" => *self = Status::NoDump,
             "good" => *self = Status::Good,
             "verified" => *self = Status::Verified,
-            _ => return false,
+            "" => return false,
+            _ => *self = Status::Other(value.to_owned()),
         }
         true
     }
+    fn to_xml_str(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(match self {
+            Status::BadDump => "baddump",
+            Status::NoDump => "nodump",
+            Status::Good => "good",
+            Status::Verified => "verified",
+            Status::Other(value) => value,
+        }))
+    }
+}
+
+#[test]
+fn test_enum_str_round_trip() {
+    fn check<T: fmt::Display + FromStr<Err = ParseEnumError> + PartialEq + fmt::Debug>(
+        values: &[T],
+    ) {
+        for value in values {
+            assert_eq!(value.to_string().parse::<T>().as_ref(), Ok(value));
+        }
+    }
+    check(&[
+        Status::BadDump,
+        Status::NoDump,
+        Status::Good,
+        Status::Verified,
+    ]);
+    check(&[Status::Other("unknownvalue".to_owned())]);
+    check(&[ForceMerging::None, ForceMerging::Split, ForceMerging::Full]);
+    check(&[
+        ForceNoDump::Obsolete,
+        ForceNoDump::Required,
+        ForceNoDump::Ignore,
+    ]);
+    check(&[ForcePacking::Zip, ForcePacking::Unzip]);
+    check(&[RomMode::Merged, RomMode::Split, RomMode::Unmerged]);
+    check(&[SampleMode::Merged, SampleMode::Unmerged]);
+    check(&[
+        DriverStatus::Good,
+        DriverStatus::Imperfect,
+        DriverStatus::Preliminary,
+    ]);
+    check(&[SaveState::Supported, SaveState::Unsupported]);
+    assert_eq!(Status::BadDump.to_string(), "baddump");
+    assert_eq!(
+        "bogus".parse::<ForceMerging>(),
+        Err(ParseEnumError("bogus".to_owned()))
+    );
+    assert!("".parse::<Status>().is_err());
 }
@@ -3,24 +3,53 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{
-    xml_attr::XmlAttr, Archive, BiosSet, ClrMamePro, DataFile, Disk, Game, Header, Release, Rom,
-    RomCenter, Sample, XmlCursor,
+    xml_attr::XmlAttr, Archive, BiosSet, ClrMamePro, DatVisitor, DataFile, DeviceRef, Disk, Driver,
+    Game, GameElementKind, Header, Release, Rom, RomCenter, Sample, Status, UnknownContent,
+    VisitControl, XmlCursor,
 };
 
 fn cursor<'a, T: XmlElement>(tag: &'static str, element: &'a mut T) -> Option<XmlCursor<'a>> {
     Some(XmlCursor { tag, element })
 }
 
+// Starts a new element in the list if `open`, otherwise returns the last one
+fn list_cursor<'a, T: XmlElement + Default>(
+    tag: &'static str,
+    list: &'a mut Vec<T>,
+    open: bool,
+) -> Option<XmlCursor<'a>> {
+    if open {
+        list.push(T::default());
+    }
+    cursor(tag, list.last_mut()?)
+}
+
 pub(crate) trait XmlElement {
     fn attr(&mut self, _: &str) -> Option<&mut dyn XmlAttr> {
         None
     }
-    fn child(&mut self, _: &str) -> Option<XmlCursor> {
+    /// Returns the child element for `tag`. With `open`, a new element is started for tags that
+    /// can repeat; otherwise the most recent one is returned.
+    fn child(&mut self, _: &str, _: bool) -> Option<XmlCursor<'_>> {
         None
     }
     fn content(&mut self) -> Option<&mut String> {
         None
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        None
+    }
+    fn child_name(&self, _: &str) -> Option<&str> {
+        None
+    }
+    fn missing_field(&self) -> Option<&'static str> {
+        None
+    }
+    fn visit(&mut self, _: &str, _: &mut dyn DatVisitor) -> VisitControl {
+        VisitControl::Continue
+    }
+    fn discard_child(&mut self, _: &str) {}
+    fn push_game(&mut self, _: Game) {}
 }
 
 impl XmlElement for String {
@@ -32,25 +61,63 @@ impl XmlElement for String {
 impl XmlElement for DataFile {
     fn attr(&mut self, key: &str) -> Option<&mut dyn XmlAttr> {
         match key {
+            "id" => Some(&mut self.id),
             "build" => Some(&mut self.build),
             "debug" => Some(&mut self.debug),
             _ => None,
         }
     }
-    fn child(&mut self, tag: &str) -> Option<XmlCursor> {
+    fn child(&mut self, tag: &str, open: bool) -> Option<XmlCursor<'_>> {
         match tag {
             "header" => cursor("header", self.header.get_or_insert_with(Header::default)),
-            "game" => {
-                self.games.push(Game::default());
-                cursor("game", self.games.last_mut().unwrap())
+            "game" => list_cursor("game", &mut self.games, open),
+            "machine" => {
+                if open {
+                    self.games.push(Game {
+                        element_kind: GameElementKind::Machine,
+                        ..Game::default()
+                    });
+                }
+                cursor("machine", self.games.last_mut()?)
             }
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
+    fn child_name(&self, tag: &str) -> Option<&str> {
+        match tag {
+            "game" | "machine" => self.games.last().map(|game| game.name.as_str()),
+            _ => None,
+        }
+    }
+    fn visit(&mut self, tag: &str, visitor: &mut dyn DatVisitor) -> VisitControl {
+        match tag {
+            "header" => match &self.header {
+                Some(header) => visitor.on_header(header),
+                None => VisitControl::Continue,
+            },
+            "game" | "machine" => match self.games.pop() {
+                Some(game) => visitor.on_game(&game),
+                None => VisitControl::Continue,
+            },
+            _ => VisitControl::Continue,
+        }
+    }
+    fn discard_child(&mut self, tag: &str) {
+        if let "game" | "machine" = tag {
+            self.games.pop();
+        }
+    }
+    fn push_game(&mut self, game: Game) {
+        self.games.push(game);
+    }
 }
 impl XmlElement for Header {
-    fn child(&mut self, tag: &str) -> Option<XmlCursor> {
+    fn child(&mut self, tag: &str, _: bool) -> Option<XmlCursor<'_>> {
         match tag {
+            "id" => cursor("id", &mut self.id),
             "name" => cursor("name", &mut self.name),
             "description" => cursor("description", &mut self.description),
             "category" => cursor("category", &mut self.category),
@@ -61,6 +128,8 @@ impl XmlElement for Header {
             "homepage" => cursor("homepage", &mut self.homepage),
             "url" => cursor("url", &mut self.url),
             "comment" => cursor("comment", &mut self.comment),
+            "subset" => cursor("subset", &mut self.subset),
+            "retool" => cursor("retool", &mut self.retool),
             "clrmamepro" => cursor(
                 "clrmamepro",
                 self.clr_mame_pro.get_or_insert_with(Default::default),
@@ -72,6 +141,9 @@ impl XmlElement for Header {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for ClrMamePro {
@@ -81,9 +153,15 @@ impl XmlElement for ClrMamePro {
             "forcemerging" => Some(&mut self.force_merging),
             "forcenodump" => Some(&mut self.force_no_dump),
             "forcepacking" => Some(&mut self.force_packing),
+            "version" => Some(&mut self.version),
+            "date" => Some(&mut self.date),
+            "comment" => Some(&mut self.comment),
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for RomCenter {
@@ -99,6 +177,9 @@ impl XmlElement for RomCenter {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for Game {
@@ -109,6 +190,7 @@ impl XmlElement for Game {
             "sourcefile" => Some(&mut self.source_file),
             "isbios" => Some(&mut self.is_bios),
             "cloneof" => Some(&mut self.clone_of),
+            "cloneofid" => Some(&mut self.clone_of_id),
             "romof" => Some(&mut self.rom_of),
             "sampleof" => Some(&mut self.sample_of),
             "board" => Some(&mut self.board),
@@ -116,40 +198,30 @@ impl XmlElement for Game {
             _ => None,
         }
     }
-    fn child(&mut self, tag: &str) -> Option<XmlCursor> {
+    fn child(&mut self, tag: &str, open: bool) -> Option<XmlCursor<'_>> {
         match tag {
             "name" => cursor("name", &mut self.name),
             "description" => cursor("description", &mut self.description),
-            "comment" => {
-                self.comments.push(String::new());
-                cursor("comment", self.comments.last_mut().unwrap())
-            }
+            "comment" => list_cursor("comment", &mut self.comments, open),
             "year" => cursor("year", &mut self.year),
             "manufacturer" => cursor("manufacturer", &mut self.manufacturer),
-            "release" => {
-                self.releases.push(Release::default());
-                cursor("release", self.releases.last_mut().unwrap())
-            }
-            "biosset" => {
-                self.bios_sets.push(BiosSet::default());
-                cursor("biosset", self.bios_sets.last_mut().unwrap())
-            }
-            "rom" => {
-                self.roms.push(Rom::default());
-                cursor("rom", self.roms.last_mut().unwrap())
-            }
-            "disk" => {
-                self.disks.push(Disk::default());
-                cursor("disk", self.disks.last_mut().unwrap())
-            }
-            "sample" => {
-                self.samples.push(Sample::default());
-                cursor("sample", self.samples.last_mut().unwrap())
-            }
-            "archive" => {
-                self.archives.push(Archive::default());
-                cursor("archive", self.archives.last_mut().unwrap())
-            }
+            "release" => list_cursor("release", &mut self.releases, open),
+            "biosset" => list_cursor("biosset", &mut self.bios_sets, open),
+            "rom" => list_cursor("rom", &mut self.roms, open),
+            "disk" => list_cursor("disk", &mut self.disks, open),
+            "sample" => list_cursor("sample", &mut self.samples, open),
+            "archive" => list_cursor("archive", &mut self.archives, open),
+            "device_ref" => list_cursor("device_ref", &mut self.device_refs, open),
+            "driver" => cursor("driver", self.driver.get_or_insert_with(Default::default)),
+            _ => None,
+        }
+    }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
+    fn child_name(&self, tag: &str) -> Option<&str> {
+        match tag {
+            "rom" => self.roms.last().map(|rom| rom.name.as_str()),
             _ => None,
         }
     }
@@ -166,6 +238,9 @@ impl XmlElement for Release {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for BiosSet {
@@ -177,6 +252,9 @@ impl XmlElement for BiosSet {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for Rom {
@@ -192,9 +270,28 @@ impl XmlElement for Rom {
             "status" => Some(&mut self.status),
             "date" => Some(&mut self.date),
             "serial" => Some(&mut self.serial),
+            "region" => Some(&mut self.region),
+            "flags" => Some(&mut self.flags),
+            "mia" => Some(&mut self.mia),
+            "bios" => Some(&mut self.bios),
+            "header" => Some(&mut self.header_ref),
+            "track" => Some(&mut self.track),
+            "number" => Some(&mut self.number),
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
+    fn missing_field(&self) -> Option<&'static str> {
+        if self.name.is_empty() {
+            Some("name")
+        } else if self.size.is_none() && self.status != Status::NoDump {
+            Some("size")
+        } else {
+            None
+        }
+    }
 }
 
 impl XmlElement for Disk {
@@ -208,6 +305,9 @@ impl XmlElement for Disk {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for Sample {
@@ -217,13 +317,53 @@ impl XmlElement for Sample {
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
 
 impl XmlElement for Archive {
     fn attr(&mut self, key: &str) -> Option<&mut dyn XmlAttr> {
         match key {
             "name" => Some(&mut self.name),
+            "number" => Some(&mut self.number),
+            "clone" => Some(&mut self.clone),
+            "region" => Some(&mut self.region),
+            "languages" => Some(&mut self.languages),
+            "version" => Some(&mut self.version),
+            "devstatus" => Some(&mut self.dev_status),
+            "additional" => Some(&mut self.additional),
+            "special" => Some(&mut self.special),
             _ => None,
         }
     }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
+}
+
+impl XmlElement for DeviceRef {
+    fn attr(&mut self, key: &str) -> Option<&mut dyn XmlAttr> {
+        match key {
+            "name" => Some(&mut self.name),
+            _ => None,
+        }
+    }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
+}
+
+impl XmlElement for Driver {
+    fn attr(&mut self, key: &str) -> Option<&mut dyn XmlAttr> {
+        match key {
+            "status" => Some(&mut self.status),
+            "emulation" => Some(&mut self.emulation),
+            "savestate" => Some(&mut self.save_state),
+            _ => None,
+        }
+    }
+    fn unknown(&mut self) -> Option<&mut UnknownContent> {
+        Some(&mut self.unknown)
+    }
 }
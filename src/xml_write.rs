@@ -0,0 +1,450 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesPI, BytesStart, BytesText, Event};
+use std::io::Write;
+
+use crate::{
+    xml_attr::XmlAttr, Archive, BiosSet, BoolStyle, ClrMamePro, DatWriterError, DataFile,
+    DeviceRef, Disk, Driver, Game, GameElementKind, Header, Release, Rom, RomCenter, Sample,
+    UnknownContent, UnknownElement,
+};
+
+const LOGIQX_DOCTYPE: &str = r#"datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd""#;
+
+pub(crate) struct XmlWriter<W: Write> {
+    writer: quick_xml::Writer<W>,
+    pending: Option<BytesStart<'static>>,
+    pub(crate) bool_style: BoolStyle,
+    pub(crate) omit_empty: bool,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub(crate) fn new(writer: W) -> XmlWriter<W> {
+        XmlWriter {
+            writer: quick_xml::Writer::new_with_indent(writer, b' ', 4),
+            pending: None,
+            bool_style: BoolStyle::default(),
+            omit_empty: true,
+        }
+    }
+    pub(crate) fn prolog(&mut self, root: &str) -> Result<(), DatWriterError> {
+        self.write_event(Event::Decl(BytesDecl::new("1.0", None, None)))?;
+        // MAME's DTD is normally inlined, so only the Logiqx one is written
+        if root != "datafile" {
+            return Ok(());
+        }
+        self.write_event(Event::DocType(BytesText::from_escaped(LOGIQX_DOCTYPE)))
+    }
+    pub(crate) fn start(&mut self, tag: &str) -> Result<(), DatWriterError> {
+        self.flush_pending()?;
+        self.write_event(Event::Start(BytesStart::new(tag)))
+    }
+    pub(crate) fn end(&mut self, tag: &str) -> Result<(), DatWriterError> {
+        self.flush_pending()?;
+        self.write_event(Event::End(BytesEnd::new(tag)))
+    }
+    pub(crate) fn game(&mut self, game: &Game) -> Result<(), DatWriterError> {
+        let tag = match game.element_kind {
+            GameElementKind::Game => "game",
+            GameElementKind::Machine => "machine",
+        };
+        self.element(tag, game)
+    }
+    pub(crate) fn finish(mut self) -> Result<W, DatWriterError> {
+        self.flush_pending()?;
+        self.writer
+            .get_mut()
+            .write_all(b"\n")
+            .map_err(quick_xml::Error::from)?;
+        Ok(self.writer.into_inner())
+    }
+    fn write_event(&mut self, event: Event) -> Result<(), DatWriterError> {
+        Ok(self.writer.write_event(event)?)
+    }
+    fn flush_pending(&mut self) -> Result<(), DatWriterError> {
+        if let Some(start) = self.pending.take() {
+            self.write_event(Event::Start(start))?;
+        }
+        Ok(())
+    }
+    pub(crate) fn element<T: XmlWrite>(
+        &mut self,
+        tag: &str,
+        element: &T,
+    ) -> Result<(), DatWriterError> {
+        self.flush_pending()?;
+        let mut start = BytesStart::new(tag.to_owned());
+        for (key, attr) in element.attrs() {
+            if self.bool_style == BoolStyle::OmitFalse && attr.is_false() {
+                continue;
+            }
+            if let Some(value) = attr.to_xml_str() {
+                start.push_attribute((key, value.as_ref()));
+            } else if !self.omit_empty && attr.is_empty_str() {
+                start.push_attribute((key, ""));
+            }
+        }
+        let unknown = element.unknown();
+        if let Some(unknown) = unknown {
+            for (key, value) in &unknown.attrs {
+                check_name(key, tag)?;
+                start.push_attribute((key.as_str(), value.as_str()));
+            }
+        }
+        self.pending = Some(start);
+        if let Some(unknown) = unknown {
+            for comment in &unknown.comments {
+                // XML doesn't allow "--" in a comment, and "-->" would end it early
+                if comment.contains("--") || comment.ends_with('-') {
+                    return Err(DatWriterError::InvalidComment(format!(
+                        "Comment \"{}\" in element \"{}\" can't be written as XML",
+                        comment, tag
+                    )));
+                }
+                self.flush_pending()?;
+                self.write_event(Event::Comment(BytesText::from_escaped(comment.as_str())))?;
+            }
+            for instruction in &unknown.processing_instructions {
+                // "?>" would end the processing instruction early
+                if instruction.contains("?>") {
+                    return Err(DatWriterError::InvalidProcessingInstruction(format!(
+                        "Processing instruction \"{}\" in element \"{}\" can't be written as XML",
+                        instruction, tag
+                    )));
+                }
+                self.flush_pending()?;
+                self.write_event(Event::PI(BytesPI::new(instruction.as_str())))?;
+            }
+        }
+        element.write_children(self)?;
+        if let Some(unknown) = unknown {
+            for child in &unknown.elements {
+                self.unknown_element(child)?;
+            }
+        }
+        match self.pending.take() {
+            Some(start) => self.write_event(Event::Empty(start)),
+            None => self.write_event(Event::End(BytesEnd::new(tag))),
+        }
+    }
+    fn unknown_element(&mut self, element: &UnknownElement) -> Result<(), DatWriterError> {
+        self.flush_pending()?;
+        check_name(&element.tag, &element.tag)?;
+        let mut start = BytesStart::new(element.tag.as_str());
+        for (key, value) in &element.attrs {
+            check_name(key, &element.tag)?;
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+        if element.text.is_empty() && element.children.is_empty() {
+            return self.write_event(Event::Empty(start));
+        }
+        self.write_event(Event::Start(start))?;
+        if !element.text.is_empty() {
+            self.write_event(Event::Text(BytesText::new(&element.text)))?;
+        }
+        for child in &element.children {
+            self.unknown_element(child)?;
+        }
+        self.write_event(Event::End(BytesEnd::new(element.tag.as_str())))
+    }
+    pub(crate) fn text_element(&mut self, tag: &str, text: &str) -> Result<(), DatWriterError> {
+        if text.is_empty() {
+            if self.omit_empty {
+                return Ok(());
+            }
+            self.flush_pending()?;
+            return self.write_event(Event::Empty(BytesStart::new(tag)));
+        }
+        self.flush_pending()?;
+        self.write_event(Event::Start(BytesStart::new(tag)))?;
+        self.write_event(Event::Text(BytesText::new(text)))?;
+        self.write_event(Event::End(BytesEnd::new(tag)))
+    }
+}
+
+// Preserved unknown names are public fields, so make sure they can't inject markup
+fn check_name(name: &str, tag: &str) -> Result<(), DatWriterError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_' || ch == ':')
+        && chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | ':' | '-' | '.'));
+    if !valid {
+        return Err(DatWriterError::InvalidName(format!(
+            "Invalid XML name \"{}\" in element \"{}\"",
+            name, tag
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) trait XmlWrite {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        Vec::new()
+    }
+    fn write_children<W: Write>(&self, _: &mut XmlWriter<W>) -> Result<(), DatWriterError> {
+        Ok(())
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        None
+    }
+}
+
+impl XmlWrite for DataFile {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("id", &self.id),
+            ("build", &self.build),
+            ("debug", &self.debug),
+        ]
+    }
+    fn write_children<W: Write>(&self, w: &mut XmlWriter<W>) -> Result<(), DatWriterError> {
+        if let Some(header) = &self.header {
+            w.element("header", header)?;
+        }
+        for game in &self.games {
+            w.game(game)?;
+        }
+        Ok(())
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Header {
+    fn write_children<W: Write>(&self, w: &mut XmlWriter<W>) -> Result<(), DatWriterError> {
+        w.text_element("id", &self.id)?;
+        w.text_element("name", &self.name)?;
+        w.text_element("description", &self.description)?;
+        w.text_element("category", &self.category)?;
+        w.text_element("version", &self.version)?;
+        w.text_element("date", &self.date)?;
+        w.text_element("author", &self.author)?;
+        w.text_element("email", &self.email)?;
+        w.text_element("homepage", &self.homepage)?;
+        w.text_element("url", &self.url)?;
+        w.text_element("comment", &self.comment)?;
+        w.text_element("subset", &self.subset)?;
+        w.text_element("retool", &self.retool)?;
+        if let Some(clr_mame_pro) = &self.clr_mame_pro {
+            w.element("clrmamepro", clr_mame_pro)?;
+        }
+        if let Some(rom_center) = &self.rom_center {
+            w.element("romcenter", rom_center)?;
+        }
+        Ok(())
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for ClrMamePro {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("header", &self.header),
+            ("forcemerging", &self.force_merging),
+            ("forcenodump", &self.force_no_dump),
+            ("forcepacking", &self.force_packing),
+            ("version", &self.version),
+            ("date", &self.date),
+            ("comment", &self.comment),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for RomCenter {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("plugin", &self.plugin),
+            ("rommode", &self.rom_mode),
+            ("biosmode", &self.bios_mode),
+            ("samplemode", &self.sample_mode),
+            ("lockrommode", &self.lock_rom_mode),
+            ("lockbiosmode", &self.lock_bios_mode),
+            ("locksamplemode", &self.lock_sample_mode),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Game {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("id", &self.id),
+            ("name", &self.name),
+            ("sourcefile", &self.source_file),
+            ("isbios", &self.is_bios),
+            ("cloneof", &self.clone_of),
+            ("cloneofid", &self.clone_of_id),
+            ("romof", &self.rom_of),
+            ("sampleof", &self.sample_of),
+            ("board", &self.board),
+            ("rebuildto", &self.rebuild_to),
+        ]
+    }
+    fn write_children<W: Write>(&self, w: &mut XmlWriter<W>) -> Result<(), DatWriterError> {
+        for comment in &self.comments {
+            w.text_element("comment", comment)?;
+        }
+        w.text_element("description", &self.description)?;
+        w.text_element("year", &self.year)?;
+        w.text_element("manufacturer", &self.manufacturer)?;
+        for release in &self.releases {
+            w.element("release", release)?;
+        }
+        for bios_set in &self.bios_sets {
+            w.element("biosset", bios_set)?;
+        }
+        for rom in &self.roms {
+            w.element("rom", rom)?;
+        }
+        for disk in &self.disks {
+            w.element("disk", disk)?;
+        }
+        for sample in &self.samples {
+            w.element("sample", sample)?;
+        }
+        for archive in &self.archives {
+            w.element("archive", archive)?;
+        }
+        for device_ref in &self.device_refs {
+            w.element("device_ref", device_ref)?;
+        }
+        if let Some(driver) = &self.driver {
+            w.element("driver", driver)?;
+        }
+        Ok(())
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Release {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("name", &self.name),
+            ("region", &self.region),
+            ("language", &self.language),
+            ("date", &self.date),
+            ("default", &self.default),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for BiosSet {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("name", &self.name),
+            ("description", &self.description),
+            ("default", &self.default),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Rom {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("name", &self.name),
+            ("size", &self.size),
+            ("crc", &self.crc),
+            ("sha1", &self.sha1),
+            ("sha256", &self.sha256),
+            ("md5", &self.md5),
+            ("merge", &self.merge),
+            ("bios", &self.bios),
+            ("header", &self.header_ref),
+            ("track", &self.track),
+            ("number", &self.number),
+            ("status", &self.status),
+            ("date", &self.date),
+            ("serial", &self.serial),
+            ("region", &self.region),
+            ("flags", &self.flags),
+            ("mia", &self.mia),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Disk {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("name", &self.name),
+            ("sha1", &self.sha1),
+            ("md5", &self.md5),
+            ("merge", &self.merge),
+            ("status", &self.status),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Sample {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![("name", &self.name)]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Archive {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("name", &self.name),
+            ("number", &self.number),
+            ("clone", &self.clone),
+            ("region", &self.region),
+            ("languages", &self.languages),
+            ("version", &self.version),
+            ("devstatus", &self.dev_status),
+            ("additional", &self.additional),
+            ("special", &self.special),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for DeviceRef {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![("name", &self.name)]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
+
+impl XmlWrite for Driver {
+    fn attrs(&self) -> Vec<(&'static str, &dyn XmlAttr)> {
+        vec![
+            ("status", &self.status),
+            ("emulation", &self.emulation),
+            ("savestate", &self.save_state),
+        ]
+    }
+    fn unknown(&self) -> Option<&UnknownContent> {
+        Some(&self.unknown)
+    }
+}
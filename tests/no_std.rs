@@ -0,0 +1,31 @@
+// SPDX-FileCopyrightText: 2019-2022 Joonas Javanainen <joonas.javanainen@gmail.com>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// The data model only needs alloc, so it can be used from no_std crates
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{borrow::ToOwned, vec};
+use retro_dat::{Crc32, DataFile, Game, Rom, Status};
+
+#[test]
+fn test_no_std_data_file() {
+    let data_file = DataFile {
+        games: vec![Game {
+            name: "Game".to_owned(),
+            roms: vec![Rom {
+                name: "Game.bin".to_owned(),
+                size: Some(1024),
+                crc: Some(Crc32(0xdeadbeef)),
+                status: Status::Verified,
+                ..Rom::default()
+            }],
+            ..Game::default()
+        }],
+        ..DataFile::default()
+    };
+    assert_eq!(data_file.games[0].roms[0].status.quality_rank(), 4);
+    assert_eq!("baddump".parse(), Ok(Status::BadDump));
+}